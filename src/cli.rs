@@ -0,0 +1,286 @@
+use tracing::error;
+use vrsc_rpc::RpcApi;
+
+use crate::{
+    controller::get_running_chains,
+    export::{snapshot_csv, Export, ExportFormat, DEFAULT_TEMPLATE},
+    verus::{classification::CurrencySettings, tvl::Tvl},
+};
+
+pub enum Command {
+    /// No subcommand: run the terminal UI.
+    Tui,
+    Export(ExportOptions),
+    Snapshot(SnapshotOptions),
+}
+
+pub struct ExportOptions {
+    /// The chain to export, the first running chain if not given.
+    pub chain: Option<String>,
+    pub format: ExportFormat,
+    pub output: String,
+}
+
+pub struct SnapshotOptions {
+    /// The chains to include, all running chains if empty.
+    pub chains: Vec<String>,
+    pub format: ExportFormat,
+}
+
+const USAGE: &str =
+    "usage: outpost [export [--chain <name>] [--format csv|json] [--output <template>]]
+       outpost snapshot [--chain <name>]... [--format csv|json]";
+
+/// Parses the arguments that follow the binary name.
+pub fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    match args.next().as_deref() {
+        None => Ok(Command::Tui),
+        Some("export") => Ok(Command::Export(parse_export_options(args)?)),
+        Some("snapshot") => Ok(Command::Snapshot(parse_snapshot_options(args)?)),
+        Some(other) => Err(format!("unknown command {}\n{}", other, USAGE)),
+    }
+}
+
+fn parse_export_options(args: impl Iterator<Item = String>) -> Result<ExportOptions, String> {
+    let mut flags = parse_flags(args, &["--chain", "--format", "--output"])?;
+
+    Ok(ExportOptions {
+        // the last one wins if the chain is given more than once
+        chain: flags.chains.pop(),
+        format: flags.format.unwrap_or(ExportFormat::Csv),
+        output: flags.output.unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+    })
+}
+
+// the flags of the subcommands, each followed by a value
+struct Flags {
+    chains: Vec<String>,
+    format: Option<ExportFormat>,
+    output: Option<String>,
+}
+
+/// Reads the flags of a subcommand, of which only the `allowed` ones may be given.
+fn parse_flags(mut args: impl Iterator<Item = String>, allowed: &[&str]) -> Result<Flags, String> {
+    let mut flags = Flags {
+        chains: vec![],
+        format: None,
+        output: None,
+    };
+
+    while let Some(arg) = args.next() {
+        if !allowed.contains(&arg.as_str()) {
+            return Err(format!("unknown argument {}\n{}", arg, USAGE));
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value\n{}", arg, USAGE))?;
+
+        match arg.as_str() {
+            "--chain" => flags.chains.push(value),
+            "--format" => flags.format = Some(value.parse()?),
+            "--output" => flags.output = Some(value),
+            other => unreachable!("{} is not a flag", other),
+        }
+    }
+
+    Ok(flags)
+}
+
+fn parse_snapshot_options(args: impl Iterator<Item = String>) -> Result<SnapshotOptions, String> {
+    let flags = parse_flags(args, &["--chain", "--format"])?;
+
+    Ok(SnapshotOptions {
+        chains: flags.chains,
+        format: flags.format.unwrap_or(ExportFormat::Json),
+    })
+}
+
+/// Exports the baskets and TVL of a chain without starting the UI. There is no transfer log to export
+/// in this case, as it is only collected while the UI runs.
+pub fn run_export(
+    testnet: bool,
+    currency_settings: &CurrencySettings,
+    options: ExportOptions,
+) -> Result<(), String> {
+    let chains = get_running_chains(testnet);
+
+    let chain = match &options.chain {
+        Some(name) => chains
+            .into_iter()
+            .find(|c| &c.read().unwrap().get_name() == name)
+            .ok_or_else(|| format!("chain {} is not running", name))?,
+        None => chains
+            .into_iter()
+            .next()
+            .ok_or_else(|| "no running chains found".to_string())?,
+    };
+
+    let mut write = chain.write().unwrap();
+
+    let height = write
+        .client()
+        .get_blockchain_info()
+        .map_err(|e| format!("{:?}", e))?
+        .blocks;
+    let baskets = write
+        .get_latest_baskets(currency_settings)
+        .map_err(|_| "could not get the baskets".to_string())?;
+    let tvl = Tvl::from_baskets(&baskets);
+    let chain_name = write.get_name();
+
+    let export = Export {
+        chain: &chain_name,
+        height,
+        baskets: &baskets,
+        tvl: &tvl,
+        log: &[],
+    };
+
+    match export.write(options.format, &options.output) {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+
+            Ok(())
+        }
+        Err(e) => {
+            error!("export failed: {:?}", e);
+
+            Err(e.to_string())
+        }
+    }
+}
+
+/// Gets the baskets of the chosen chains once and prints them with their TVL to stdout.
+/// Any failing RPC call fails the whole snapshot, so that a cron job can notice it.
+pub fn run_snapshot(
+    testnet: bool,
+    currency_settings: &CurrencySettings,
+    options: SnapshotOptions,
+) -> Result<(), String> {
+    let chains = get_running_chains(testnet);
+
+    for name in options.chains.iter() {
+        if !chains.iter().any(|c| &c.read().unwrap().get_name() == name) {
+            return Err(format!("chain {} is not running", name));
+        }
+    }
+
+    let mut snapshots = vec![];
+    for chain in chains.iter() {
+        let mut write = chain.write().unwrap();
+        let chain_name = write.get_name();
+
+        if !options.chains.is_empty() && !options.chains.contains(&chain_name) {
+            continue;
+        }
+
+        let height = write
+            .client()
+            .get_blockchain_info()
+            .map_err(|e| format!("{}: {:?}", chain_name, e))?
+            .blocks;
+        let baskets = write
+            .get_latest_baskets(currency_settings)
+            .map_err(|_| format!("{}: could not get the baskets", chain_name))?;
+        let tvl = Tvl::from_baskets(&baskets);
+
+        snapshots.push((chain_name, height, baskets, tvl));
+    }
+
+    if snapshots.is_empty() {
+        return Err("no running chains found".to_string());
+    }
+
+    let exports = snapshots
+        .iter()
+        .map(|(chain, height, baskets, tvl)| Export {
+            chain,
+            height: *height,
+            baskets,
+            tvl,
+            log: &[],
+        })
+        .collect::<Vec<_>>();
+
+    match options.format {
+        ExportFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&exports.iter().map(|e| e.to_json()).collect::<Vec<_>>())
+                .map_err(|e| e.to_string())?
+        ),
+        ExportFormat::Csv => print!("{}", snapshot_csv(&exports)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn runs_the_ui_without_a_command() {
+        assert!(matches!(parse(&[]), Ok(Command::Tui)));
+    }
+
+    #[test]
+    fn export_has_defaults() {
+        match parse(&["export"]) {
+            Ok(Command::Export(options)) => {
+                assert_eq!(options.chain, None);
+                assert_eq!(options.format, ExportFormat::Csv);
+                assert_eq!(options.output, DEFAULT_TEMPLATE);
+            }
+            _ => panic!("expected an export"),
+        }
+    }
+
+    #[test]
+    fn export_reads_its_flags() {
+        let command = parse(&[
+            "export",
+            "--chain",
+            "vrsctest",
+            "--format",
+            "JSON",
+            "--output",
+            "{chain}-{kind}",
+        ]);
+
+        match command {
+            Ok(Command::Export(options)) => {
+                assert_eq!(options.chain.as_deref(), Some("vrsctest"));
+                assert_eq!(options.format, ExportFormat::Json);
+                assert_eq!(options.output, "{chain}-{kind}");
+            }
+            _ => panic!("expected an export"),
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_arguments() {
+        assert!(parse(&["import"]).is_err());
+        assert!(parse(&["export", "--verbose"]).is_err());
+        assert!(parse(&["export", "--chain"]).is_err());
+        assert!(parse(&["export", "--format", "xml"]).is_err());
+        assert!(parse(&["snapshot", "--output", "snapshot.json"]).is_err());
+    }
+
+    #[test]
+    fn snapshot_takes_several_chains() {
+        match parse(&["snapshot", "--chain", "VRSC", "--chain", "vDEX"]) {
+            Ok(Command::Snapshot(options)) => {
+                assert_eq!(options.chains, vec!["VRSC", "vDEX"]);
+                assert_eq!(options.format, ExportFormat::Json);
+            }
+            _ => panic!("expected a snapshot"),
+        }
+    }
+}
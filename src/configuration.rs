@@ -1,13 +1,98 @@
-use serde::Deserialize;
+use std::{collections::HashMap, fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{export::DEFAULT_TEMPLATE, menu::BasketMode, verus::classification::CurrencySettings};
 
 #[derive(Deserialize, Clone)]
 pub struct ApplicationSettings {
     pub testnet: bool,
+    #[serde(default)]
+    pub valuation: ValuationSettings,
+    #[serde(default)]
+    pub watchlists: Vec<Watchlist>,
+    #[serde(default)]
+    pub log: LogSettings,
+    #[serde(default)]
+    pub currencies: CurrencySettings,
+    #[serde(default)]
+    pub export: ExportSettings,
 }
 
-pub fn get_configuration() -> Result<ApplicationSettings, config::ConfigError> {
+#[derive(Deserialize, Clone)]
+pub struct ExportSettings {
+    /// The file name of exports from the UI, which can contain `{chain}`, `{height}` and `{kind}`.
+    #[serde(default = "default_export_template")]
+    pub template: String,
+    /// The directory exports from the UI are written to, the current directory if not given.
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            template: default_export_template(),
+            directory: None,
+        }
+    }
+}
+
+impl ExportSettings {
+    /// The template including the directory, as `Export::write` takes it.
+    pub fn output(&self) -> String {
+        match &self.directory {
+            Some(directory) => directory.join(&self.template).to_string_lossy().to_string(),
+            None => self.template.clone(),
+        }
+    }
+}
+
+fn default_export_template() -> String {
+    DEFAULT_TEMPLATE.to_string()
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct LogSettings {
+    /// Only show transfers that involve one of the currencies checked in the selector.
+    #[serde(default)]
+    pub filter_by_selection: bool,
+    /// Only show transfers once they have this many confirmations; 0 also shows transfers in the mempool.
+    #[serde(default)]
+    pub min_confirmations: u64,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub struct ValuationSettings {
+    /// The fully qualified name of the currency to express the TVL in, e.g. `DAI.vETH`.
+    pub quote_currency: Option<String>,
+    /// Prices in the quote currency that are not derived from baskets.
+    #[serde(default)]
+    pub prices: HashMap<String, f64>,
+}
+
+/// A saved selection of currencies and baskets on a chain.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Watchlist {
+    pub name: String,
+    pub chain: String,
+    /// fully qualified names of the checked currencies in the selector
+    #[serde(default)]
+    pub currencies: Vec<String>,
+    /// basket names or patterns
+    #[serde(default)]
+    pub baskets: Vec<String>,
+    pub basket_mode: BasketMode,
+}
+
+fn configuration_directory() -> PathBuf {
     let base_path = std::env::current_dir().expect("Failed to determine the current directory");
-    let configuration_directory = base_path.join("config");
+
+    base_path.join("config")
+}
+
+pub fn get_configuration() -> Result<ApplicationSettings, config::ConfigError> {
+    let configuration_directory = configuration_directory();
 
     // Detect the running environment.
     // Default to `local` if unspecified.
@@ -23,6 +108,10 @@ pub fn get_configuration() -> Result<ApplicationSettings, config::ConfigError> {
         .add_source(config::File::from(
             configuration_directory.join(&environment_filename),
         ))
+        // Watchlists are saved from the UI, so they are kept in a separate file that can be overwritten.
+        .add_source(
+            config::File::from(configuration_directory.join("watchlists.json")).required(false),
+        )
         // Add in settings from environment variables (with a prefix of APP and '__' as separator)
         // E.g. `APP_APPLICATION__PORT=5001 would set `Settings.application.port`
         .add_source(
@@ -35,6 +124,41 @@ pub fn get_configuration() -> Result<ApplicationSettings, config::ConfigError> {
     settings.try_deserialize::<ApplicationSettings>()
 }
 
+pub fn write_watchlists(watchlists: &[Watchlist]) -> io::Result<()> {
+    let contents = serde_json::to_string_pretty(&json!({ "watchlists": watchlists }))?;
+
+    fs::write(configuration_directory().join("watchlists.json"), contents)
+}
+
+/// The currency names of a chain, keyed by currency id. The chain id tells a cache of a reset testnet
+/// apart from the current one.
+#[derive(Serialize, Deserialize, Default)]
+pub struct NameCache {
+    pub chainid: String,
+    pub names: HashMap<String, String>,
+}
+
+fn name_cache_path(chain_name: &str) -> PathBuf {
+    configuration_directory()
+        .join("names")
+        .join(format!("{}.json", chain_name.to_lowercase()))
+}
+
+pub fn read_name_cache(chain_name: &str) -> Option<NameCache> {
+    let contents = fs::read_to_string(name_cache_path(chain_name)).ok()?;
+
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn write_name_cache(chain_name: &str, cache: &NameCache) -> io::Result<()> {
+    let path = name_cache_path(chain_name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, serde_json::to_string_pretty(cache)?)
+}
+
 pub fn write_configuration(_config: &ApplicationSettings) {
     todo!()
 }
@@ -1,12 +1,13 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{HashMap, HashSet, VecDeque},
+    path::PathBuf,
     rc::Rc,
     str::FromStr,
     sync::{mpsc, RwLock},
 };
 
 use chrono::Local;
-use serde_json::Value;
+use serde_json::{json, Value};
 use tracing::{debug, error, info};
 use vrsc_rpc::{
     bitcoin::{hashes::sha256d::Hash, Txid},
@@ -18,34 +19,78 @@ use vrsc_rpc::{
 };
 
 use crate::{
+    configuration::{
+        write_watchlists, ApplicationSettings, ExportSettings, ValuationSettings, Watchlist,
+    },
+    export::{Export, ExportFormat},
     menu::BasketMode,
     ui::{UIMessage, UI},
     verus::pbaas::local_pbaas_chains,
-    verus::{vrsc::VerusChain, Chain},
-    views::log::LogMessage,
+    verus::{
+        arbitrage::find_opportunities,
+        classification::CurrencySettings,
+        crosschain::compare_chains,
+        events::{self, ChainEvent, LaunchEvent},
+        lifecycle,
+        tvl::Tvl,
+        valuation::Valuation,
+        vrsc::VerusChain,
+        Basket, Chain,
+    },
+    views::log::{upsert, LogMessage, MessageType, TransferState, MAX_CONFIRMATIONS},
 };
 
 pub type IdNames = Rc<RwLock<HashMap<String, String>>>;
 
+/// The number of blocks after which the currency list of the active chain is checked for new currencies,
+/// also when no new currency definition was seen.
+const CURRENCY_REFRESH_INTERVAL: u32 = 10;
+
+/// The number of blocks after which all the baskets of a chain are fetched again,
+/// also when the blocks in between did not touch them.
+const FULL_BASKET_REFRESH_INTERVAL: u32 = 20;
+
 pub struct Controller {
     pub c_rx: mpsc::Receiver<ControllerMessage>,
+    c_tx: mpsc::Sender<ControllerMessage>,
     pub l_tx: mpsc::Sender<LogMessage>,
     pub ui: UI,
     pbaas_chains: Vec<Rc<RwLock<Box<dyn Chain>>>>,
     active_chain: Rc<RwLock<Box<dyn Chain>>>,
-    id_names: IdNames,
+    // the latest baskets per chain name
+    chain_baskets: HashMap<String, Vec<Basket>>,
+    // the number of blocks since all the baskets of a chain were fetched, per chain name
+    blocks_since_full_refresh: HashMap<String, u32>,
+    // the baskets per chain name before the latest refresh, to show what changed in the last block
+    previous_chain_baskets: HashMap<String, Vec<Basket>>,
+    // the ids of the currencies that were last sent to the selector, none after a chain change
+    selector_currency_ids: Option<HashSet<Address>>,
+    // the currency list is checked for new currencies every `CURRENCY_REFRESH_INTERVAL` blocks,
+    // or in the next block when a new currency was defined on the active chain
+    blocks_since_currency_refresh: u32,
+    new_currency_defined: bool,
+    // the ids and names of the currencies in their preconversion phase, per chain name
+    prelaunch_currencies: HashMap<String, Vec<(String, String)>>,
+    // the latest TVL per chain name
+    chain_tvl: HashMap<String, Tvl>,
+    // a copy of the messages in the log, kept for exports
+    transfer_log: VecDeque<LogMessage>,
+    valuation_settings: ValuationSettings,
+    export_settings: ExportSettings,
+    currency_settings: CurrencySettings,
+    watchlists: Vec<Watchlist>,
     // basket_mode: BasketMode,
 }
 
 impl Controller {
-    pub fn new(testnet: bool) -> Self {
+    pub fn new(config: ApplicationSettings) -> Self {
+        let testnet = config.testnet;
+
         let (c_tx, c_rx) = mpsc::channel::<ControllerMessage>();
 
         let (l_tx, l_rx) = mpsc::channel::<LogMessage>();
 
-        let id_names = Rc::new(RwLock::new(HashMap::new()));
-
-        let pbaas_chains = get_running_chains(testnet, Rc::clone(&id_names));
+        let pbaas_chains = get_running_chains(testnet);
         if let Some(first) = pbaas_chains.first() {
             let first = Rc::clone(first);
 
@@ -58,11 +103,24 @@ impl Controller {
 
             let controller = Controller {
                 c_rx,
+                c_tx: c_tx.clone(),
                 l_tx,
-                ui: UI::new(c_tx.clone(), l_rx),
+                ui: UI::new(c_tx.clone(), l_rx, config.log),
                 pbaas_chains: pbaas_chains,
                 active_chain: first,
-                id_names,
+                chain_baskets: HashMap::new(),
+                blocks_since_full_refresh: HashMap::new(),
+                previous_chain_baskets: HashMap::new(),
+                selector_currency_ids: None,
+                blocks_since_currency_refresh: 0,
+                new_currency_defined: false,
+                prelaunch_currencies: HashMap::new(),
+                chain_tvl: HashMap::new(),
+                transfer_log: VecDeque::new(),
+                valuation_settings: config.valuation,
+                export_settings: config.export,
+                currency_settings: config.currencies,
+                watchlists: config.watchlists,
             };
 
             return controller;
@@ -74,16 +132,20 @@ impl Controller {
     pub fn start(&mut self) {
         self.ui.siv.set_autorefresh(false);
 
+        let chain_names = self.chain_names();
+        let _ = self.ui.ui_tx.send(UIMessage::UpdateChainTabs(
+            chain_names,
+            self.active_chain_name(),
+        ));
+
+        let _ = self.ui.ui_tx.send(UIMessage::UpdateWatchlists(
+            self.c_tx.clone(),
+            self.watchlist_names(),
+        ));
+
         self.update_selection_screen();
         self.update_baskets();
 
-        if let Ok(chain) = self.active_chain.read() {
-            let _ = self
-                .ui
-                .ui_tx
-                .send(UIMessage::UpdateTLV(get_tlv(chain.as_ref())));
-        }
-
         while self.ui.step() {
             if let Some(message) = self.c_rx.try_iter().next() {
                 match message {
@@ -93,50 +155,107 @@ impl Controller {
                         if let Err(e) = self.ui.ui_tx.send(UIMessage::ApplyFilter) {
                             error!("{:?}", e)
                         }
-
-                        if let Ok(chain) = self.active_chain.read() {
-                            let _ = self
-                                .ui
-                                .ui_tx
-                                .send(UIMessage::UpdateTLV(get_tlv(chain.as_ref())));
-                        }
                     }
                     ControllerMessage::NewBlock(chain_name, blockhash) => {
-                        let active_chain_name = self.active_chain.read().unwrap().get_name();
-                        if active_chain_name == chain_name {
+                        let block = self.get_block(&chain_name, &blockhash);
+
+                        if self.active_chain_name() == chain_name {
                             info!("new block arrived on {}: {}", chain_name, blockhash);
 
-                            self.update_baskets();
+                            self.blocks_since_currency_refresh += 1;
+                            if self.new_currency_defined
+                                || self.blocks_since_currency_refresh >= CURRENCY_REFRESH_INTERVAL
+                            {
+                                self.new_currency_defined = false;
+                                self.refresh_selection_screen();
+                            }
+
+                            self.refresh_chain_for_block(&chain_name, block.as_ref());
+                            self.show_chain(&chain_name);
+                        } else {
+                            debug!("new block arrived on {}: {}", chain_name, blockhash);
+
+                            self.refresh_chain_for_block(&chain_name, block.as_ref());
+
+                            let _ = self.ui.ui_tx.send(UIMessage::ChainUpdated(chain_name));
                         }
 
-                        if let Ok(chain) = self.active_chain.read() {
-                            let _ = self
-                                .ui
-                                .ui_tx
-                                .send(UIMessage::UpdateTLV(get_tlv(chain.as_ref())));
+                        let _ = self
+                            .ui
+                            .ui_tx
+                            .send(UIMessage::UpdateCrossChain(compare_chains(
+                                &self.chain_baskets,
+                            )));
+
+                        self.update_confirmations(&chain_name);
+                        if let Some(block) = &block {
+                            self.check_preconversions(&chain_name, block);
                         }
                     }
                     ControllerMessage::NewTransaction(chain_name, txid) => {
-                        let active_chain_name = self.active_chain.read().unwrap().get_name();
-                        if active_chain_name == chain_name {
-                            debug!("process this tx: {}", txid);
+                        debug!("process this tx on {}: {}", chain_name, txid);
 
-                            let hash = Hash::from_str(&txid).unwrap();
-                            let txid = Txid::from_hash(hash);
-
-                            if let Ok(raw_tx) = self
-                                .active_chain
+                        if let Some(chain) = self.chain_by_name(&chain_name) {
+                            // the parts of the transaction that link transfers to their export and import
+                            // are read from the plain JSON, the rest from its typed form
+                            let tx_json = chain
                                 .read()
                                 .unwrap()
                                 .client()
-                                .get_raw_transaction_verbose(&txid)
-                            {
-                                process_transaction(
-                                    raw_tx,
-                                    Rc::clone(&self.id_names),
-                                    Rc::clone(&self.active_chain),
-                                    self.l_tx.clone(),
+                                .call::<Value>("getrawtransaction", &[json!(txid), json!(1)])
+                                .map_err(|e| error!("getrawtransaction failed: {:?}", e))
+                                .ok();
+                            let raw_tx = tx_json.as_ref().and_then(|tx_json| {
+                                serde_json::from_value::<GetRawTransactionResultVerbose>(
+                                    tx_json.clone(),
                                 )
+                                .map_err(|e| error!("could not read transaction {}: {:?}", txid, e))
+                                .ok()
+                            });
+
+                            if let (Some(raw_tx), Some(tx_json)) = (raw_tx, &tx_json) {
+                                let mut messages = process_transaction(
+                                    raw_tx,
+                                    tx_json,
+                                    chain.read().unwrap().id_names(),
+                                    Rc::clone(&chain),
+                                );
+
+                                self.mark_exported(tx_json);
+
+                                // an import of a transfer that is in the log settles it, instead of
+                                // being logged on its own
+                                let settled_exports = self.mark_settled(tx_json, &chain);
+                                messages.retain(|message| {
+                                    !(matches!(message._type, MessageType::Settle)
+                                        && message
+                                            .export_txid
+                                            .as_ref()
+                                            .map(|export_txid| {
+                                                settled_exports.contains(export_txid)
+                                            })
+                                            .unwrap_or(false))
+                                });
+
+                                let events = self.event_messages(&chain, tx_json);
+
+                                // a currency that is defined in the mempool can only be listed once it is mined
+                                if chain_name == self.active_chain_name()
+                                    && events.iter().any(|message| {
+                                        matches!(
+                                            message._type,
+                                            MessageType::NewCurrency | MessageType::NewBasket
+                                        )
+                                    })
+                                {
+                                    self.new_currency_defined = true;
+                                }
+
+                                messages.extend(events);
+
+                                for message in messages {
+                                    self.log(message);
+                                }
                             }
                         }
 
@@ -148,37 +267,123 @@ impl Controller {
                             .unwrap();
                     }
                     ControllerMessage::ChainChange(chain) => {
-                        debug!("change the chain to {:?}", &chain);
+                        self.change_chain(&chain);
+                    }
+                    ControllerMessage::ChainStep(step) => {
+                        let chain_names = self.chain_names();
+                        let active_chain_name = self.active_chain_name();
 
-                        self.active_chain = Rc::clone(
-                            self.pbaas_chains
-                                .iter()
-                                .find(|c| c.read().unwrap().get_name() == chain)
-                                .unwrap(),
-                        );
+                        if let Some(index) =
+                            chain_names.iter().position(|c| c == &active_chain_name)
+                        {
+                            let len = chain_names.len() as isize;
+                            let next = (index as isize + step).rem_euclid(len) as usize;
 
-                        if let Ok(chain) = self.active_chain.read() {
-                            let _ = self
-                                .ui
-                                .ui_tx
-                                .send(UIMessage::UpdateTLV(get_tlv(chain.as_ref())));
+                            self.change_chain(&chain_names[next]);
                         }
-
-                        self.update_selection_screen();
-                        self.update_baskets();
                     }
                     ControllerMessage::PBaaSDialog(c_tx) => {
-                        let labels = self
-                            .pbaas_chains
-                            .iter()
-                            .map(|c| c.read().unwrap().get_name())
-                            .collect();
+                        let labels = self.chain_names();
 
                         self.ui
                             .ui_tx
                             .send(UIMessage::PBaasDialog(c_tx, labels))
                             .unwrap();
                     }
+                    ControllerMessage::CrossChainDialog => {
+                        for chain_name in self.chain_names() {
+                            if !self.chain_baskets.contains_key(&chain_name) {
+                                self.refresh_chain(&chain_name);
+                            }
+                        }
+
+                        self.ui
+                            .ui_tx
+                            .send(UIMessage::CrossChainDialog(compare_chains(
+                                &self.chain_baskets,
+                            )))
+                            .unwrap();
+                    }
+                    ControllerMessage::BasketDialog => {
+                        let names = self
+                            .chain_baskets
+                            .get(&self.active_chain_name())
+                            .map(|baskets| baskets.iter().map(|b| b.name.clone()).collect())
+                            .unwrap_or_default();
+
+                        let _ = self.ui.ui_tx.send(UIMessage::BasketDialog(names));
+                    }
+                    ControllerMessage::SaveWatchlist(name) => {
+                        let watchlist = self.ui.watchlist(name, self.active_chain_name());
+
+                        // saving under an existing name replaces that watchlist
+                        self.watchlists.retain(|w| w.name != watchlist.name);
+                        self.watchlists.push(watchlist);
+
+                        if let Err(e) = write_watchlists(&self.watchlists) {
+                            error!("could not save watchlists: {:?}", e);
+
+                            let _ = self.ui.ui_tx.send(UIMessage::ShowMessage(format!(
+                                "Could not save watchlists: {}",
+                                e
+                            )));
+                        }
+
+                        let _ = self.ui.ui_tx.send(UIMessage::UpdateWatchlists(
+                            self.c_tx.clone(),
+                            self.watchlist_names(),
+                        ));
+                    }
+                    ControllerMessage::ApplyWatchlist(name) => {
+                        if let Some(watchlist) =
+                            self.watchlists.iter().find(|w| w.name == name).cloned()
+                        {
+                            if watchlist.chain != self.active_chain_name() {
+                                self.change_chain(&watchlist.chain);
+                            }
+
+                            let _ = self.ui.ui_tx.send(UIMessage::ApplyWatchlist(watchlist));
+                        }
+                    }
+                    ControllerMessage::Export(format) => {
+                        let message = match self.export(format) {
+                            Ok(paths) => format!(
+                                "Exported to:\n{}",
+                                paths
+                                    .iter()
+                                    .map(|path| path.display().to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n")
+                            ),
+                            Err(e) => {
+                                error!("export failed: {:?}", e);
+
+                                format!("Export failed: {}", e)
+                            }
+                        };
+
+                        let _ = self.ui.ui_tx.send(UIMessage::ShowMessage(message));
+                    }
+                    ControllerMessage::TransactionDetails(chain_name, txid) => {
+                        if let Some(chain) = self.chain_by_name(&chain_name) {
+                            let details = chain
+                                .read()
+                                .unwrap()
+                                .client()
+                                .call::<Value>("getrawtransaction", &[json!(txid), json!(1)])
+                                .map_err(|e| error!("getrawtransaction failed: {:?}", e))
+                                .and_then(|tx| {
+                                    serde_json::to_string_pretty(&tx).map_err(|e| error!("{:?}", e))
+                                });
+
+                            if let Ok(details) = details {
+                                let _ = self
+                                    .ui
+                                    .ui_tx
+                                    .send(UIMessage::TransactionDetails(txid, details));
+                            }
+                        }
+                    }
                     ControllerMessage::BasketModeChange(basket_mode) => {
                         // self.basket_mode = basket_mode;
 
@@ -188,6 +393,11 @@ impl Controller {
             }
         }
 
+        // the names that were looked up while running are kept for the next run
+        for chain in self.pbaas_chains.iter() {
+            chain.read().unwrap().save_name_cache();
+        }
+
         // 1d878bf932c406647374cafa9019ee5b00c581309e01f772d6e147f34b6bc601 = reservetransfer > spenttxid
         // 0b80f3f5b0932f47c6d75f67085979cf5067b60077f3196f080fa788f078804d
         // 6c070618db343c1ba288f7da713729540058c4e54ea63b5ac0c5757fc5166d76
@@ -198,71 +408,83 @@ impl Controller {
         // 1b04030001011504af02625e74df9de1cf78921e0690ab94b2d6c603cc3604030901011504af02625e74df9de1cf78921e0690ab94b2d6c6031a0176f89c6dc26d4d775b3dceef7ad4f1d3efd35a0380e9c8bf0775
         fn process_transaction(
             raw_tx: GetRawTransactionResultVerbose,
+            tx_json: &Value,
             id_names: IdNames,
-            active_chain: Rc<RwLock<Box<dyn Chain>>>,
-            l_tx: mpsc::Sender<LogMessage>,
-        ) {
-            if raw_tx.confirmations.is_none() {
-                for vout in &raw_tx.vout {
-                    if let Some(reserve_transfer) = &vout.script_pubkey.reservetransfer {
-                        debug!("a transfer was initiated: {}", raw_tx.txid);
+            chain: Rc<RwLock<Box<dyn Chain>>>,
+        ) -> Vec<LogMessage> {
+            let chain_name = chain.read().unwrap().get_name();
+            let mut messages = vec![];
 
-                        if let Ok(mut write) = id_names.write() {
-                            let currencyname = write
-                                .entry(reserve_transfer.destinationcurrencyid.to_string())
-                                .or_insert_with(|| {
-                                    active_chain
-                                        .read()
-                                        .unwrap()
-                                        .client()
-                                        .get_currency(
-                                            &reserve_transfer.destinationcurrencyid.to_string(),
-                                        )
-                                        .unwrap()
-                                        .fullyqualifiedname
-                                })
-                                .clone();
+            let confirmations = raw_tx.confirmations.map(|c| c as u64).unwrap_or(0);
 
-                            debug!("currencyname: {}", &currencyname);
+            // a transfer is logged when it enters the mempool, and again when it is mined, which replaces the first message
+            for (n, vout) in raw_tx.vout.iter().enumerate() {
+                if let Some(reserve_transfer) = &vout.script_pubkey.reservetransfer {
+                    debug!("a transfer was initiated: {}", raw_tx.txid);
 
-                            let amount_in_currency = write
-                                .entry(
-                                    reserve_transfer
-                                        .currencyvalues
-                                        .keys()
-                                        .last()
-                                        .unwrap()
-                                        .to_string(),
-                                )
-                                .or_insert_with(|| {
-                                    active_chain
-                                        .read()
-                                        .unwrap()
-                                        .client()
-                                        .get_currency(
-                                            &reserve_transfer.destinationcurrencyid.to_string(),
-                                        )
-                                        .unwrap()
-                                        .fullyqualifiedname
-                                })
-                                .clone();
+                    if let Ok(mut write) = id_names.write() {
+                        let currencyname = write
+                            .entry(reserve_transfer.destinationcurrencyid.to_string())
+                            .or_insert_with(|| {
+                                chain
+                                    .read()
+                                    .unwrap()
+                                    .client()
+                                    .get_currency(
+                                        &reserve_transfer.destinationcurrencyid.to_string(),
+                                    )
+                                    .unwrap()
+                                    .fullyqualifiedname
+                            })
+                            .clone();
 
-                            l_tx.send(LogMessage {
-                                time: format!("{}", Local::now().format("%H:%M:%S")),
-                                _type: crate::views::log::MessageType::Initiate,
-                                reserve: currencyname,
-                                amount_in_currency: amount_in_currency,
-                                amount_in: vout.value,
-                                amount_out: None,
+                        debug!("currencyname: {}", &currencyname);
+
+                        // the currency that is sent
+                        let source_currencyid = reserve_transfer
+                            .currencyvalues
+                            .keys()
+                            .last()
+                            .unwrap()
+                            .to_string();
+                        let amount_in_currency = write
+                            .entry(source_currencyid.clone())
+                            .or_insert_with(|| {
+                                chain
+                                    .read()
+                                    .unwrap()
+                                    .client()
+                                    .get_currency(&source_currencyid)
+                                    .unwrap()
+                                    .fullyqualifiedname
                             })
-                            .unwrap();
-                        }
+                            .clone();
+
+                        messages.push(LogMessage {
+                            time: format!("{}", Local::now().format("%H:%M:%S")),
+                            chain: chain_name.clone(),
+                            _type: MessageType::Initiate,
+                            reserve: currencyname,
+                            amount_in_currency: amount_in_currency,
+                            amount_in: vout.value,
+                            amount_out: None,
+                            txid: raw_tx.txid.to_string(),
+                            vout: n,
+                            confirmations,
+                            state: TransferState::Pending,
+                            destination: lifecycle::destination(tx_json, n),
+                            destination_currencyid: reserve_transfer
+                                .destinationcurrencyid
+                                .to_string(),
+                            export_txid: None,
+                            import_txid: None,
+                        });
                     }
                 }
             }
 
             if raw_tx.confirmations.is_some() {
-                for vout in &raw_tx.vout {
+                for (n, vout) in raw_tx.vout.iter().enumerate() {
                     if let Some(crosschain_import) = &vout.script_pubkey.crosschainimport {
                         info!("a transfer was settled: {}", raw_tx.txid);
                         // if crosschain_import.exporttxid
@@ -271,7 +493,7 @@ impl Controller {
                             let currencyname = write
                                 .entry(crosschain_import.importcurrencyid.to_string())
                                 .or_insert_with(|| {
-                                    active_chain
+                                    chain
                                         .read()
                                         .unwrap()
                                         .client()
@@ -283,15 +505,25 @@ impl Controller {
                                 })
                                 .clone();
 
-                            l_tx.send(LogMessage {
+                            messages.push(LogMessage {
                                 time: format!("{}", Local::now().format("%H:%M:%S")),
-                                _type: crate::views::log::MessageType::Settle,
+                                chain: chain_name.clone(),
+                                _type: MessageType::Settle,
                                 reserve: currencyname,
                                 amount_in_currency: String::new(),
                                 amount_in: vout.value,
                                 amount_out: None,
-                            })
-                            .unwrap();
+                                txid: raw_tx.txid.to_string(),
+                                vout: n,
+                                confirmations,
+                                state: TransferState::Settled,
+                                destination: None,
+                                destination_currencyid: crosschain_import
+                                    .importcurrencyid
+                                    .to_string(),
+                                export_txid: lifecycle::imported_export(tx_json, n),
+                                import_txid: Some(raw_tx.txid.to_string()),
+                            });
                         }
                     }
                     // if let Some(object) = value["reserveoutput"].as_object() {
@@ -302,12 +534,37 @@ impl Controller {
                     // 1b3f5cdc77c2e11c1225246db7703b8686d3650c7e48df68d2db1b77f001c370
                 }
             }
+
+            messages
         }
     }
 
     pub fn update_selection_screen(&mut self) {
-        if let Ok(write) = self.active_chain.write() {
-            if let Ok(currencies) = write.get_latest_currencies() {
+        self.selector_currency_ids = None;
+        self.new_currency_defined = false;
+        self.refresh_selection_screen();
+    }
+
+    /// Sends the currencies of the active chain to the selector if they changed since they were last sent.
+    /// The selector keeps the checked currencies by their id, so they stay checked.
+    fn refresh_selection_screen(&mut self) {
+        self.blocks_since_currency_refresh = 0;
+
+        let currencies = match self.active_chain.write() {
+            Ok(write) => write.get_latest_currencies(&self.currency_settings),
+            Err(_) => return,
+        };
+
+        if let Ok(currencies) = currencies {
+            let currency_ids = currencies
+                .iter()
+                .map(|c| c.currencydefinition.currencyid.clone())
+                .collect::<HashSet<_>>();
+
+            if self.selector_currency_ids.as_ref() != Some(&currency_ids) {
+                debug!("{} currencies in the selector", currency_ids.len());
+                self.selector_currency_ids = Some(currency_ids);
+
                 if let Err(e) = self
                     .ui
                     .ui_tx
@@ -323,90 +580,493 @@ impl Controller {
         }
     }
 
+    /// Refreshes the active chain and shows its baskets, opportunities and TVL.
     pub fn update_baskets(&mut self) {
-        if let Ok(mut write) = self.active_chain.write() {
-            if let Ok(baskets) = write.get_latest_baskets() {
-                if let Err(e) = self
-                    .ui
-                    .ui_tx
-                    .send(UIMessage::UpdateReserveOverview(baskets))
-                {
-                    error!("{:?}", e)
+        let chain_name = self.active_chain_name();
+
+        self.refresh_chain(&chain_name);
+        self.show_chain(&chain_name);
+    }
+
+    /// Fetches the latest baskets and TVL of a chain, whether it is shown or not.
+    fn refresh_chain(&mut self, chain_name: &str) {
+        if let Some(chain) = self.chain_by_name(chain_name) {
+            if let Ok(mut write) = chain.write() {
+                match write.get_latest_baskets(&self.currency_settings) {
+                    Ok(baskets) => {
+                        self.blocks_since_full_refresh
+                            .insert(chain_name.to_string(), 0);
+                        self.store_baskets(chain_name, baskets);
+                    }
+                    Err(e) => error!("could not get baskets for {}: {:?}", chain_name, e),
                 }
             }
         }
     }
-}
 
-fn get_tlv(chain: &dyn Chain) -> BTreeMap<String, f64> {
-    let client = chain.client();
-    let currencyname = chain.get_name();
-    let resp: Value = client
-        .call("getcurrencyconverters", &[currencyname.into()])
-        .unwrap();
-
-    let mut currencies: BTreeMap<Address, Amount> = BTreeMap::new();
-
-    for obj in resp.as_array().unwrap().iter() {
-        let obj = obj.as_object().unwrap();
-        let last_nota = obj["lastnotarization"].as_object().unwrap();
-        let currency_state = last_nota["currencystate"].as_object().unwrap();
-        let reserve_currencies = currency_state["reservecurrencies"].as_array().unwrap();
-        for currency in reserve_currencies.iter() {
-            let currencyid = Address::from_str(
-                currency
-                    .as_object()
-                    .unwrap()
-                    .get("currencyid")
-                    .unwrap()
-                    .as_str()
-                    .unwrap(),
-            )
-            .unwrap();
-
-            let reserves = Amount::from_vrsc(
-                currency
-                    .as_object()
-                    .unwrap()
-                    .get("reserves")
-                    .unwrap()
-                    .as_f64()
-                    .unwrap(),
-            )
-            .unwrap();
-
-            currencies
-                .entry(currencyid)
-                .and_modify(|amt| *amt += reserves)
-                .or_insert(reserves);
+    /// Fetches only the baskets of a chain that the transactions in a new block touched.
+    /// All the baskets are fetched every `FULL_BASKET_REFRESH_INTERVAL` blocks, when a basket was defined,
+    /// or when the block could not be read.
+    fn refresh_chain_for_block(&mut self, chain_name: &str, block: Option<&Value>) {
+        let blocks = self
+            .blocks_since_full_refresh
+            .entry(chain_name.to_string())
+            .or_insert(0);
+        *blocks += 1;
+
+        if *blocks >= FULL_BASKET_REFRESH_INTERVAL || !self.chain_baskets.contains_key(chain_name) {
+            self.refresh_chain(chain_name);
+            return;
+        }
+
+        let chain = match self.chain_by_name(chain_name) {
+            Some(chain) => chain,
+            None => return,
+        };
+
+        let block = match block {
+            Some(block) => block,
+            None => {
+                self.refresh_chain(chain_name);
+                return;
+            }
+        };
+
+        let basket_defined = block["tx"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(events::chain_events)
+            .any(|(_, event)| match event {
+                ChainEvent::NewCurrency { options, .. } => {
+                    self.currency_settings.is_basket(options)
+                }
+                _ => false,
+            });
+
+        if basket_defined {
+            self.refresh_chain(chain_name);
+            return;
+        }
+
+        let touched = lifecycle::touched_currencies(block);
+        let refreshed = chain
+            .read()
+            .unwrap()
+            .refresh_baskets(&self.chain_baskets[chain_name], &touched);
+
+        match refreshed {
+            Ok(baskets) => self.store_baskets(chain_name, baskets),
+            Err(e) => error!("could not refresh baskets for {}: {:?}", chain_name, e),
         }
     }
 
-    currencies
-        .into_iter()
-        .map(|(k, v)| {
-            (
-                client
-                    .get_currency(&k.to_string())
-                    .unwrap()
-                    .fullyqualifiedname,
-                // k.to_string(),
-                v.as_vrsc(),
-            )
-        })
-        .collect()
+    /// Gets a block with its transactions, as a `getblock` result with verbosity 2.
+    fn get_block(&self, chain_name: &str, blockhash: &str) -> Option<Value> {
+        let chain = self.chain_by_name(chain_name)?;
+        let block = chain
+            .read()
+            .unwrap()
+            .client()
+            .call::<Value>("getblock", &[json!(blockhash), json!(2)]);
+
+        block.map_err(|e| error!("getblock failed: {:?}", e)).ok()
+    }
+
+    fn store_baskets(&mut self, chain_name: &str, baskets: Vec<Basket>) {
+        self.chain_tvl
+            .insert(chain_name.to_string(), Tvl::from_baskets(&baskets));
+        if let Some(previous) = self.chain_baskets.insert(chain_name.to_string(), baskets) {
+            self.previous_chain_baskets
+                .insert(chain_name.to_string(), previous);
+        }
+    }
+
+    /// Sends the last known state of a chain to the UI.
+    fn show_chain(&mut self, chain_name: &str) {
+        if let Some(baskets) = self.chain_baskets.get(chain_name) {
+            if let Err(e) = self
+                .ui
+                .ui_tx
+                .send(UIMessage::UpdateOpportunities(find_opportunities(baskets)))
+            {
+                error!("{:?}", e)
+            }
+
+            if let Err(e) = self.ui.ui_tx.send(UIMessage::UpdateReserveOverview(
+                baskets.clone(),
+                self.previous_chain_baskets
+                    .get(chain_name)
+                    .cloned()
+                    .unwrap_or_default(),
+            )) {
+                error!("{:?}", e)
+            }
+        }
+
+        if let Some(tvl) = self.chain_tvl.get(chain_name) {
+            let _ = self.ui.ui_tx.send(UIMessage::UpdateTLV(
+                tvl.clone(),
+                self.valuation(chain_name),
+            ));
+        }
+    }
+
+    fn change_chain(&mut self, chain_name: &str) {
+        debug!("change the chain to {:?}", chain_name);
+
+        if let Some(chain) = self.chain_by_name(chain_name) {
+            self.active_chain = chain;
+
+            let _ = self
+                .ui
+                .ui_tx
+                .send(UIMessage::ActiveChainChange(chain_name.to_string()));
+
+            self.update_selection_screen();
+
+            // other chains are kept up to date per block, so there is no need to wait for a refresh
+            if !self.chain_baskets.contains_key(chain_name) {
+                self.refresh_chain(chain_name);
+            }
+
+            self.show_chain(chain_name);
+        }
+    }
+
+    /// Prices the currencies of a chain in the configured quote currency, if there is one.
+    fn valuation(&self, chain_name: &str) -> Option<Valuation> {
+        let quote = self.valuation_settings.quote_currency.as_ref()?;
+        let baskets = self.chain_baskets.get(chain_name)?;
+
+        Some(Valuation::derive(
+            baskets,
+            quote,
+            &self.valuation_settings.prices,
+        ))
+    }
+
+    fn log(&mut self, message: LogMessage) {
+        upsert(&mut self.transfer_log, message.clone());
+
+        if let Err(e) = self.l_tx.send(message) {
+            error!("{:?}", e)
+        }
+    }
+
+    /// Log messages for the currency definitions, identities and notarizations in a transaction.
+    fn event_messages(
+        &self,
+        chain: &Rc<RwLock<Box<dyn Chain>>>,
+        tx_json: &Value,
+    ) -> Vec<LogMessage> {
+        let chain_name = chain.read().unwrap().get_name();
+        let txid = tx_json["txid"].as_str().unwrap_or_default();
+        let confirmations = tx_json["confirmations"].as_u64().unwrap_or(0);
+
+        events::chain_events(tx_json)
+            .into_iter()
+            .map(|(n, event)| {
+                let (_type, subject) = match event {
+                    ChainEvent::NewCurrency { name, options } => {
+                        if self.currency_settings.is_basket(options) {
+                            (MessageType::NewBasket, name)
+                        } else {
+                            (MessageType::NewCurrency, name)
+                        }
+                    }
+                    ChainEvent::Identity { name } => (MessageType::Identity, name),
+                    ChainEvent::Notarization { currencyid } => {
+                        let name = Address::from_str(&currencyid)
+                            .map(|address| chain.write().unwrap().currency_id_to_name(address))
+                            .unwrap_or(currencyid);
+
+                        (MessageType::Notarization, name)
+                    }
+                };
+
+                event_message(&chain_name, _type, subject, txid, n, confirmations)
+            })
+            .collect()
+    }
+
+    /// Logs the currencies of a chain whose preconversion phase ended in a block, and whether they launched
+    /// or failed to launch and are refunded. The currencies that are in their preconversion phase when outpost
+    /// starts are listed once per chain, the ones that are defined later are picked up from the blocks.
+    fn check_preconversions(&mut self, chain_name: &str, block: &Value) {
+        if !self.prelaunch_currencies.contains_key(chain_name) {
+            let chain = match self.chain_by_name(chain_name) {
+                Some(chain) => chain,
+                None => return,
+            };
+
+            let prelaunch = chain
+                .read()
+                .unwrap()
+                .client()
+                .call::<Value>("listcurrencies", &[json!({ "launchstate": "prelaunch" })])
+                .map(|list| events::currencies(&list))
+                .map_err(|e| error!("listcurrencies failed: {:?}", e));
+
+            match prelaunch {
+                Ok(prelaunch) => {
+                    self.prelaunch_currencies
+                        .insert(chain_name.to_string(), prelaunch);
+                }
+                Err(()) => return,
+            }
+        }
+
+        for (txid, vout, event) in events::launch_events(block) {
+            let prelaunch = self
+                .prelaunch_currencies
+                .entry(chain_name.to_string())
+                .or_default();
+
+            match event {
+                LaunchEvent::Defined { currencyid, name } => {
+                    if !prelaunch.iter().any(|(id, _)| id == &currencyid) {
+                        prelaunch.push((currencyid, name));
+                    }
+                }
+                // later notarizations of a launched currency are cleared too, only the first one ends the preconversion
+                LaunchEvent::Cleared {
+                    currencyid,
+                    launched,
+                } => {
+                    if let Some(index) = prelaunch.iter().position(|(id, _)| id == &currencyid) {
+                        let (_, name) = prelaunch.remove(index);
+                        info!(
+                            "preconversion of {} ended on {}, launched: {}",
+                            name, chain_name, launched
+                        );
+
+                        let _type = if launched {
+                            MessageType::PreconversionEnd
+                        } else {
+                            MessageType::LaunchFailed
+                        };
+
+                        // final as soon as it is seen in a block
+                        self.log(event_message(
+                            chain_name,
+                            _type,
+                            name,
+                            &txid,
+                            vout,
+                            MAX_CONFIRMATIONS,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks the logged transfers that an export bundles as exported.
+    fn mark_exported(&mut self, tx_json: &Value) {
+        let export_txid = tx_json["txid"].as_str().unwrap_or_default().to_string();
+        let spent = lifecycle::exported_transfers(tx_json);
+
+        let exported = self
+            .transfer_log
+            .iter()
+            .filter(|message| message.state == TransferState::Pending)
+            .filter(|message| {
+                spent
+                    .iter()
+                    .any(|(txid, vout)| &message.txid == txid && message.vout == *vout)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for mut message in exported {
+            debug!("transfer {} exported in {}", message.txid, export_txid);
+
+            message.state = TransferState::Exported;
+            message.export_txid = Some(export_txid.clone());
+            self.log(message);
+        }
+    }
+
+    /// Marks the logged transfers of the exports that a transaction imports as settled, with the amount they paid out.
+    /// Returns the txids of the exports that settled a logged transfer.
+    fn mark_settled(
+        &mut self,
+        tx_json: &Value,
+        chain: &Rc<RwLock<Box<dyn Chain>>>,
+    ) -> HashSet<String> {
+        let exports = lifecycle::imported_exports(tx_json);
+        if exports.is_empty() {
+            return HashSet::new();
+        }
+
+        let settled = self
+            .transfer_log
+            .iter()
+            .filter(|message| {
+                matches!(message._type, MessageType::Initiate)
+                    && message
+                        .export_txid
+                        .as_ref()
+                        .map(|export_txid| exports.contains(export_txid))
+                        .unwrap_or(false)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if settled.is_empty() {
+            return HashSet::new();
+        }
+
+        let native_currencyid = chain
+            .read()
+            .unwrap()
+            .client()
+            .get_blockchain_info()
+            .map(|info| info.chainid.to_string())
+            .unwrap_or_default();
+        let import_txid = tx_json["txid"].as_str().map(String::from);
+
+        // an import pays every transfer with its own output, so when several transfers pay the same currency
+        // to the same address, the outputs are matched to the transfers in the order they were logged
+        let mut payouts = HashMap::new();
+        let mut settled_exports = HashSet::new();
+        for mut message in settled {
+            debug!("transfer {} settled in {:?}", message.txid, import_txid);
+
+            message.state = TransferState::Settled;
+            message.import_txid = import_txid.clone();
+            message.amount_out = message.destination.clone().and_then(|destination| {
+                let currencyid = message.destination_currencyid.clone();
+
+                payouts
+                    .entry((destination.clone(), currencyid.clone()))
+                    .or_insert_with(|| {
+                        lifecycle::payouts(tx_json, &destination, &currencyid, &native_currencyid)
+                            .into_iter()
+                    })
+                    .next()
+            });
+            settled_exports.extend(message.export_txid.clone());
+            self.log(message);
+        }
+
+        settled_exports
+    }
+
+    /// Updates the number of confirmations of the logged transfers of a chain that are not yet
+    /// confirmed often enough to be shown with any minimum.
+    fn update_confirmations(&mut self, chain_name: &str) {
+        let chain = match self.chain_by_name(chain_name) {
+            Some(chain) => chain,
+            None => return,
+        };
+
+        let mut txids = self
+            .transfer_log
+            .iter()
+            .filter(|message| {
+                message.chain == chain_name && message.confirmations < MAX_CONFIRMATIONS
+            })
+            .map(|message| message.txid.clone())
+            .collect::<Vec<_>>();
+        txids.sort_unstable();
+        txids.dedup();
+
+        for txid in txids {
+            let raw_tx = Hash::from_str(&txid)
+                .map_err(|e| error!("invalid txid {}: {:?}", txid, e))
+                .and_then(|hash| {
+                    chain
+                        .read()
+                        .unwrap()
+                        .client()
+                        .get_raw_transaction_verbose(&Txid::from_hash(hash))
+                        .map_err(|e| error!("getrawtransaction failed for {}: {:?}", txid, e))
+                });
+
+            if let Ok(raw_tx) = raw_tx {
+                let confirmations = raw_tx.confirmations.map(|c| c as u64).unwrap_or(0);
+
+                let updated = self
+                    .transfer_log
+                    .iter()
+                    .filter(|message| message.chain == chain_name && message.txid == txid)
+                    .filter(|message| message.confirmations != confirmations)
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                for mut message in updated {
+                    message.confirmations = confirmations;
+                    self.log(message);
+                }
+            }
+        }
+    }
+
+    /// Writes the last known state of the active chain to disk.
+    fn export(&self, format: ExportFormat) -> std::io::Result<Vec<PathBuf>> {
+        let chain_name = self.active_chain_name();
+        let height = self
+            .active_chain
+            .read()
+            .unwrap()
+            .client()
+            .get_blockchain_info()
+            .map(|info| info.blocks)
+            .unwrap_or_default();
+        let log = self
+            .transfer_log
+            .iter()
+            .filter(|message| message.chain == chain_name)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        Export {
+            chain: &chain_name,
+            height,
+            baskets: self
+                .chain_baskets
+                .get(&chain_name)
+                .map(|baskets| baskets.as_slice())
+                .unwrap_or_default(),
+            tvl: &self.chain_tvl.get(&chain_name).cloned().unwrap_or_default(),
+            log: &log,
+        }
+        .write(format, &self.export_settings.output())
+    }
+
+    fn watchlist_names(&self) -> Vec<String> {
+        self.watchlists.iter().map(|w| w.name.clone()).collect()
+    }
+
+    fn chain_by_name(&self, chain_name: &str) -> Option<Rc<RwLock<Box<dyn Chain>>>> {
+        self.pbaas_chains
+            .iter()
+            .find(|c| c.read().unwrap().get_name() == chain_name)
+            .map(Rc::clone)
+    }
+
+    fn chain_names(&self) -> Vec<String> {
+        self.pbaas_chains
+            .iter()
+            .map(|c| c.read().unwrap().get_name())
+            .collect()
+    }
+
+    fn active_chain_name(&self) -> String {
+        self.active_chain.read().unwrap().get_name()
+    }
 }
 
-pub fn get_running_chains(testnet: bool, id_names: IdNames) -> Vec<Rc<RwLock<Box<dyn Chain>>>> {
+pub fn get_running_chains(testnet: bool) -> Vec<Rc<RwLock<Box<dyn Chain>>>> {
     let mut all_chains: Vec<Rc<RwLock<Box<dyn Chain>>>> = vec![];
 
-    let v_chain: Rc<RwLock<Box<dyn Chain>>> = Rc::new(RwLock::new(Box::new(VerusChain::new(
-        testnet,
-        Rc::clone(&id_names),
-    ))));
+    let v_chain: Rc<RwLock<Box<dyn Chain>>> =
+        Rc::new(RwLock::new(Box::new(VerusChain::new(testnet))));
     all_chains.push(v_chain);
 
-    let local_chains = local_pbaas_chains(testnet, Rc::clone(&id_names));
+    let local_chains = local_pbaas_chains(testnet);
     local_chains.into_iter().for_each(|mut c| {
         c.set_name();
         all_chains.push(Rc::new(RwLock::new(Box::new(c))));
@@ -414,11 +1074,45 @@ pub fn get_running_chains(testnet: bool, id_names: IdNames) -> Vec<Rc<RwLock<Box
 
     dbg!(&all_chains);
 
-    all_chains
+    let running_chains: Vec<Rc<RwLock<Box<dyn Chain>>>> = all_chains
         .into_iter()
         // .inspect(|chain| )
         .filter(|chain| dbg!(chain.read().unwrap().client().ping().is_ok()))
-        .collect()
+        .collect();
+
+    for chain in running_chains.iter() {
+        chain.read().unwrap().warm_name_cache();
+    }
+
+    running_chains
+}
+
+// a log message about something else than a transfer
+fn event_message(
+    chain_name: &str,
+    _type: MessageType,
+    subject: String,
+    txid: &str,
+    vout: usize,
+    confirmations: u64,
+) -> LogMessage {
+    LogMessage {
+        time: format!("{}", Local::now().format("%H:%M:%S")),
+        chain: chain_name.to_string(),
+        _type,
+        reserve: subject,
+        amount_in_currency: String::new(),
+        amount_in: Amount::from_sat(0),
+        amount_out: None,
+        txid: txid.to_string(),
+        vout,
+        confirmations,
+        state: TransferState::Pending,
+        destination: None,
+        destination_currencyid: String::new(),
+        export_txid: None,
+        import_txid: None,
+    }
 }
 
 pub enum ControllerMessage {
@@ -426,6 +1120,17 @@ pub enum ControllerMessage {
     NewTransaction(String, String),
     CurrencySelectionChange,
     ChainChange(String),
+    // move the active chain this many places in the list of running chains
+    ChainStep(isize),
     PBaaSDialog(mpsc::Sender<ControllerMessage>),
+    CrossChainDialog,
     BasketModeChange(BasketMode),
+    BasketDialog,
+    // save the current selection on the active chain as a watchlist with this name
+    SaveWatchlist(String),
+    ApplyWatchlist(String),
+    // export the active chain to disk
+    Export(ExportFormat),
+    // show the decoded transaction with this txid on the given chain
+    TransactionDetails(String, String),
 }
@@ -0,0 +1,264 @@
+use std::{fs, io, path::PathBuf, str::FromStr};
+
+use serde_json::{json, Value};
+
+use crate::{
+    verus::{tvl::Tvl, Basket},
+    views::log::LogMessage,
+};
+
+pub const DEFAULT_TEMPLATE: &str = "outpost-{chain}-{height}-{kind}";
+
+const BASKET_HEADER: [&str; 8] = [
+    "basket",
+    "basket_currencyid",
+    "supply",
+    "reserve",
+    "reserve_currencyid",
+    "weight",
+    "price",
+    "amount",
+];
+
+// the totals per currency have an empty basket column
+const TVL_HEADER: [&str; 3] = ["basket", "currency", "amount"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!(
+                "{} is not a valid export format (csv, json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Everything outpost knows about a chain at a certain height.
+pub struct Export<'a> {
+    pub chain: &'a str,
+    pub height: u64,
+    pub baskets: &'a [Basket],
+    pub tvl: &'a Tvl,
+    pub log: &'a [LogMessage],
+}
+
+impl<'a> Export<'a> {
+    /// Writes the export to one JSON file, or to a CSV file per kind (baskets, tvl, log).
+    /// The template can contain `{chain}`, `{height}` and `{kind}`, the extension is added to it.
+    pub fn write(&self, format: ExportFormat, template: &str) -> io::Result<Vec<PathBuf>> {
+        let files = match format {
+            ExportFormat::Json => vec![(
+                "snapshot",
+                serde_json::to_string_pretty(&self.to_json())
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?,
+            )],
+            ExportFormat::Csv => vec![
+                ("baskets", self.baskets_csv()),
+                ("tvl", self.tvl_csv()),
+                ("log", self.log_csv()),
+            ],
+        };
+
+        let mut paths = vec![];
+        for (kind, contents) in files {
+            let path = PathBuf::from(format!(
+                "{}.{}",
+                template
+                    .replace("{chain}", self.chain)
+                    .replace("{height}", &self.height.to_string())
+                    .replace("{kind}", kind),
+                format.extension()
+            ));
+
+            fs::write(&path, contents)?;
+            paths.push(path);
+        }
+
+        Ok(paths)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "chain": self.chain,
+            "height": self.height,
+            "baskets": self.baskets.iter().map(basket_json).collect::<Vec<_>>(),
+            "tvl": self.tvl,
+            "log": self.log.iter().map(|message| json!({
+                "time": message.time,
+                "chain": message.chain,
+                "currency": message.reserve,
+                "amount_in": message.amount_in.as_vrsc(),
+                "amount_in_currency": message.amount_in_currency,
+                "amount_out": message.amount_out,
+                "txid": message.txid,
+                "confirmations": message.confirmations,
+                "state": message.state.label(),
+                "export_txid": message.export_txid,
+                "import_txid": message.import_txid,
+            })).collect::<Vec<_>>(),
+        })
+    }
+
+    fn baskets_csv(&self) -> String {
+        let mut rows = vec![csv_row(&BASKET_HEADER)];
+        rows.extend(self.basket_rows().iter().map(|row| csv_row(row)));
+
+        rows.join("")
+    }
+
+    fn basket_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![];
+
+        for basket in self.baskets {
+            for rc in basket.currency_state.reservecurrencies.iter() {
+                rows.push(vec![
+                    basket.name.clone(),
+                    basket.currencyid.to_string(),
+                    basket.currency_state.supply.as_vrsc().to_string(),
+                    basket
+                        .currencynames
+                        .get(&rc.currencyid)
+                        .cloned()
+                        .unwrap_or_else(|| rc.currencyid.to_string()),
+                    rc.currencyid.to_string(),
+                    rc.weight.to_string(),
+                    rc.priceinreserve.as_vrsc().to_string(),
+                    rc.reserves.as_vrsc().to_string(),
+                ]);
+            }
+        }
+
+        rows
+    }
+
+    fn tvl_csv(&self) -> String {
+        let mut rows = vec![csv_row(&TVL_HEADER)];
+        rows.extend(self.tvl_rows().iter().map(|row| csv_row(row)));
+
+        rows.join("")
+    }
+
+    fn tvl_rows(&self) -> Vec<Vec<String>> {
+        let totals =
+            self.tvl.per_reserve.iter().map(|(currency, amount)| {
+                vec![String::new(), currency.clone(), amount.to_string()]
+            });
+
+        let breakdown = self.tvl.per_basket.iter().flat_map(|(basket, reserves)| {
+            reserves.iter().map(move |(currency, amount)| {
+                vec![basket.clone(), currency.clone(), amount.to_string()]
+            })
+        });
+
+        totals.chain(breakdown).collect()
+    }
+
+    fn log_csv(&self) -> String {
+        let mut rows = vec![csv_row(&[
+            "time",
+            "chain",
+            "currency",
+            "amount_in",
+            "amount_in_currency",
+            "amount_out",
+            "txid",
+            "confirmations",
+            "state",
+            "export_txid",
+            "import_txid",
+        ])];
+
+        for message in self.log {
+            rows.push(csv_row(&[
+                &message.time,
+                &message.chain,
+                &message.reserve,
+                &message.amount_in.as_vrsc().to_string(),
+                &message.amount_in_currency,
+                &message
+                    .amount_out
+                    .map(|amount| amount.to_string())
+                    .unwrap_or_default(),
+                &message.txid,
+                &message.confirmations.to_string(),
+                &message.state.label().to_string(),
+                &message.export_txid.clone().unwrap_or_default(),
+                &message.import_txid.clone().unwrap_or_default(),
+            ]));
+        }
+
+        rows.join("")
+    }
+}
+
+fn basket_json(basket: &Basket) -> Value {
+    json!({
+        "name": basket.name,
+        "currencyid": basket.currencyid.to_string(),
+        "supply": basket.currency_state.supply.as_vrsc(),
+        "reserves": basket.currency_state.reservecurrencies.iter().map(|rc| json!({
+            "name": basket.currencynames.get(&rc.currencyid),
+            "currencyid": rc.currencyid.to_string(),
+            "weight": rc.weight,
+            "price": rc.priceinreserve.as_vrsc(),
+            "amount": rc.reserves.as_vrsc(),
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Puts the baskets and the TVL of several chains in one CSV document, with the chain in the first column.
+pub fn snapshot_csv(exports: &[Export]) -> String {
+    let mut rows = vec![csv_row(&[&["chain"][..], &BASKET_HEADER[..]].concat())];
+    for export in exports {
+        for row in export.basket_rows() {
+            rows.push(csv_row(&[vec![export.chain.to_string()], row].concat()));
+        }
+    }
+
+    rows.push(String::from("\n"));
+
+    rows.push(csv_row(&[&["chain"][..], &TVL_HEADER[..]].concat()));
+    for export in exports {
+        for row in export.tvl_rows() {
+            rows.push(csv_row(&[vec![export.chain.to_string()], row].concat()));
+        }
+    }
+
+    rows.join("")
+}
+
+fn csv_row(fields: &[impl AsRef<str>]) -> String {
+    let fields = fields
+        .iter()
+        .map(|field| field.as_ref())
+        .map(|field| {
+            if field.contains(|c: char| c == ',' || c == '"' || c == '\n') {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    format!("{}\n", fields.join(","))
+}
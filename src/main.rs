@@ -1,10 +1,13 @@
+pub mod cli;
 pub mod configuration;
 pub mod controller;
+pub mod export;
 pub mod menu;
 pub mod ui;
 pub mod verus;
 pub mod views;
 
+use cli::Command;
 use configuration::get_configuration;
 use controller::Controller;
 use tracing::debug;
@@ -17,9 +20,33 @@ fn main() {
 
     debug!("logging enabled");
 
-    let mut controller = Controller::new(config.testnet);
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
 
-    controller.start();
+    match command {
+        Command::Tui => {
+            let mut controller = Controller::new(config);
+
+            controller.start();
+        }
+        Command::Export(options) => {
+            if let Err(e) = cli::run_export(config.testnet, &config.currencies, options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Snapshot(options) => {
+            if let Err(e) = cli::run_snapshot(config.testnet, &config.currencies, options) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 }
 
 fn logging_setup() {
@@ -35,5 +62,7 @@ fn logging_setup() {
 
     tracing_subscriber::fmt::fmt()
         .with_env_filter(EnvFilter::from_default_env())
+        // stdout is for the output of the subcommands
+        .with_writer(std::io::stderr)
         .init();
 }
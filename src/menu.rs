@@ -1,45 +1,247 @@
 use std::sync::mpsc;
 
-use cursive::{menu::Tree, views::Dialog, Cursive};
+use cursive::{
+    menu::Tree,
+    view::{Nameable, Resizable},
+    views::{Dialog, EditView, ScrollView, SelectView},
+    Cursive,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::controller::ControllerMessage;
+use crate::{
+    controller::ControllerMessage,
+    export::ExportFormat,
+    views::{
+        log::{LogView, MAX_CONFIRMATIONS},
+        reserves::Reserves,
+        reservetable::PriceMode,
+    },
+};
 
 pub fn set_menubar(siv: &mut Cursive, c_tx: mpsc::Sender<ControllerMessage>) {
     siv.menubar()
         .add_subtree(
             "File",
-            Tree::new().leaf("Quit", |s| {
-                s.add_layer(
-                    Dialog::text("Do you really want to quit?")
-                        .button("Yes", |s| s.quit())
-                        .dismiss_button("No"),
+            Tree::new()
+                .subtree(
+                    "Export",
+                    Tree::new()
+                        .leaf("CSV", {
+                            let c_tx = c_tx.clone();
+                            move |_| {
+                                let _ = c_tx.send(ControllerMessage::Export(ExportFormat::Csv));
+                            }
+                        })
+                        .leaf("JSON", {
+                            let c_tx = c_tx.clone();
+                            move |_| {
+                                let _ = c_tx.send(ControllerMessage::Export(ExportFormat::Json));
+                            }
+                        }),
                 )
-            }),
+                .delimiter()
+                .leaf("Quit", |s| {
+                    s.add_layer(
+                        Dialog::text("Do you really want to quit?")
+                            .button("Yes", |s| s.quit())
+                            .dismiss_button("No"),
+                    )
+                }),
         )
         .add_subtree(
             "Edit",
-            Tree::new().subtree(
-                "Basket mode",
-                Tree::new()
-                    .leaf("Selected currencies", {
-                        let c_tx = c_tx.clone();
-                        move |_| {
-                            let _ = c_tx
-                                .send(ControllerMessage::BasketModeChange(BasketMode::Selected));
+            Tree::new()
+                .subtree(
+                    "Basket mode",
+                    Tree::new()
+                        .leaf("Selected currencies", {
+                            let c_tx = c_tx.clone();
+                            move |_| {
+                                let _ = c_tx.send(ControllerMessage::BasketModeChange(
+                                    BasketMode::Selected,
+                                ));
+                            }
+                        })
+                        .leaf("All currencies", {
+                            let c_tx = c_tx.clone();
+                            move |_| {
+                                let _ =
+                                    c_tx.send(ControllerMessage::BasketModeChange(BasketMode::All));
+                            }
+                        })
+                        .leaf("Selected baskets", {
+                            let c_tx = c_tx.clone();
+                            move |_| {
+                                let _ = c_tx
+                                    .send(ControllerMessage::BasketModeChange(BasketMode::Baskets));
+                            }
+                        }), // .leaf("Complete", |_| {}),
+                )
+                .leaf("Select baskets...", {
+                    let c_tx = c_tx.clone();
+                    move |_| {
+                        let _ = c_tx.send(ControllerMessage::BasketDialog);
+                    }
+                })
+                .subtree("Watchlists", watchlists_tree(c_tx.clone(), vec![]))
+                .subtree(
+                    "Log",
+                    Tree::new()
+                        .leaf("Filter by selected currencies", |s| {
+                            s.call_on_name("LOG", |log: &mut LogView| {
+                                log.toggle_filter_by_selection()
+                            });
+                        })
+                        .leaf("Minimum confirmations...", min_confirmations_dialog),
+                ),
+        )
+        .add_subtree(
+            "View",
+            Tree::new()
+                .leaf("Cross-chain prices", {
+                    let c_tx = c_tx.clone();
+                    move |_| {
+                        let _ = c_tx.send(ControllerMessage::CrossChainDialog);
+                    }
+                })
+                .subtree(
+                    "Prices",
+                    Tree::new()
+                        .leaf("Basket in reserve", |s| {
+                            set_price_mode(s, PriceMode::BasketInReserve)
+                        })
+                        .leaf("Reserve in basket", |s| {
+                            set_price_mode(s, PriceMode::ReserveInBasket)
+                        })
+                        .leaf("Reserve in other reserve...", cross_reserve_dialog),
+                )
+                .leaf("Changed baskets only", |s| {
+                    s.call_on_name("RESERVES", |view: &mut Reserves| {
+                        view.toggle_changed_only();
+                        view.update_view();
+                    });
+                }),
+        );
+}
+
+fn min_confirmations_dialog(siv: &mut Cursive) {
+    let current = siv
+        .call_on_name("LOG", |log: &mut LogView| log.min_confirmations())
+        .unwrap_or_default();
+
+    siv.add_layer(
+        Dialog::around(
+            EditView::new()
+                .content(current.to_string())
+                .with_name("min_confirmations")
+                .min_width(10),
+        )
+        .title(format!("Minimum confirmations (0-{})", MAX_CONFIRMATIONS))
+        .button("Ok", |s| {
+            let min_confirmations = s
+                .call_on_name("min_confirmations", |edit: &mut EditView| {
+                    edit.get_content()
+                })
+                .and_then(|content| content.parse::<u64>().ok());
+
+            if let Some(min_confirmations) = min_confirmations {
+                s.call_on_name("LOG", |log: &mut LogView| {
+                    log.set_min_confirmations(min_confirmations)
+                });
+            }
+            s.pop_layer();
+        })
+        .dismiss_button("Cancel"),
+    );
+}
+
+fn set_price_mode(siv: &mut Cursive, price_mode: PriceMode) {
+    siv.call_on_name("RESERVES", |view: &mut Reserves| {
+        view.set_price_mode(price_mode);
+        view.update_view();
+    });
+}
+
+/// Lets the user pick the reserve currency that the prices in the reserve tables are expressed in.
+pub fn cross_reserve_dialog(siv: &mut Cursive) {
+    let names = siv
+        .call_on_name("RESERVES", |view: &mut Reserves| view.reserve_names())
+        .unwrap_or_default();
+
+    siv.add_layer(
+        Dialog::around(
+            ScrollView::new(SelectView::new().with_all_str(names).on_submit(
+                |s, reserve: &String| {
+                    s.pop_layer();
+                    set_price_mode(s, PriceMode::Cross(reserve.clone()));
+                },
+            ))
+            .max_height(20),
+        )
+        .title("Prices in")
+        .dismiss_button("Cancel"),
+    );
+}
+
+/// Replaces the watchlists in the Edit menu.
+pub fn set_watchlists(
+    siv: &mut Cursive,
+    c_tx: mpsc::Sender<ControllerMessage>,
+    names: Vec<String>,
+) {
+    if let Some(tree) = siv
+        .menubar()
+        .find_subtree("Edit")
+        .and_then(|edit| edit.find_subtree("Watchlists"))
+    {
+        *tree = watchlists_tree(c_tx, names);
+    }
+}
+
+fn watchlists_tree(c_tx: mpsc::Sender<ControllerMessage>, names: Vec<String>) -> Tree {
+    let mut tree = Tree::new().leaf("Save current as...", {
+        let c_tx = c_tx.clone();
+        move |s| {
+            let c_tx = c_tx.clone();
+            s.add_layer(
+                Dialog::around(EditView::new().with_name("watchlist_name").min_width(30))
+                    .title("Save watchlist")
+                    .button("Save", move |s| {
+                        let name = s
+                            .call_on_name("watchlist_name", |edit: &mut EditView| {
+                                edit.get_content()
+                            })
+                            .unwrap_or_default();
+
+                        if !name.is_empty() {
+                            let _ = c_tx.send(ControllerMessage::SaveWatchlist(name.to_string()));
                         }
+                        s.pop_layer();
                     })
-                    .leaf("All currencies", {
-                        let c_tx = c_tx.clone();
-                        move |_| {
-                            let _ = c_tx.send(ControllerMessage::BasketModeChange(BasketMode::All));
-                        }
-                    }), // .leaf("Complete", |_| {}),
-            ),
-        );
+                    .dismiss_button("Cancel"),
+            )
+        }
+    });
+
+    if !names.is_empty() {
+        tree.add_delimiter();
+    }
+
+    for name in names {
+        let c_tx = c_tx.clone();
+        tree.add_leaf(name.clone(), move |_| {
+            let _ = c_tx.send(ControllerMessage::ApplyWatchlist(name.clone()));
+        });
+    }
+
+    tree
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BasketMode {
     Selected,
     All,
+    // only the baskets that were picked by name
+    Baskets,
 }
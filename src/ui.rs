@@ -1,23 +1,29 @@
-use std::{collections::BTreeMap, sync::mpsc};
+use std::sync::mpsc;
 
 use cursive::{
     view::{Nameable, Resizable},
-    views::{LinearLayout, Panel},
+    views::{Dialog, EditView, LinearLayout, Panel, ScrollView, TextView},
     CursiveRunnable, CursiveRunner,
 };
 use tracing::debug;
 use vrsc_rpc::json::Currency;
 
 use crate::{
+    configuration::{LogSettings, Watchlist},
     controller::ControllerMessage,
     menu::BasketMode,
-    verus::Basket,
+    verus::{
+        arbitrage::Opportunity, crosschain::PriceComparison, tvl::Tvl, valuation::Valuation, Basket,
+    },
     views::{
-        filterbox::FilterBox,
+        basket_dialog::BasketDialog,
+        crosschain::CrossChain,
         log::{LogMessage, LogView},
+        opportunities::Opportunities,
         pbaas_dialog::PbaasDialog,
         reserves::Reserves,
         selector::Selector,
+        tabs::ChainTabs,
         tvl::TVL,
     },
 };
@@ -32,7 +38,11 @@ pub struct UI {
 }
 
 impl UI {
-    pub fn new(c_tx: mpsc::Sender<ControllerMessage>, l_rx: mpsc::Receiver<LogMessage>) -> Self {
+    pub fn new(
+        c_tx: mpsc::Sender<ControllerMessage>,
+        l_rx: mpsc::Receiver<LogMessage>,
+        log_settings: LogSettings,
+    ) -> Self {
         let (ui_tx, ui_rx) = mpsc::channel::<UIMessage>();
         let mut siv = cursive::ncurses().into_runner();
         siv.update_theme(|theme| theme.shadow = false);
@@ -50,6 +60,50 @@ impl UI {
             }
         });
 
+        let c_tx_clone = c_tx.clone();
+
+        siv.add_global_callback('c', move |s| {
+            debug!("c was pressed");
+            if s.find_name::<CrossChain>("CROSSCHAIN").is_some() {
+                debug!("cross-chain dialog found, ignore");
+            } else {
+                c_tx_clone
+                    .send(ControllerMessage::CrossChainDialog)
+                    .unwrap();
+            }
+        });
+
+        let c_tx_clone = c_tx.clone();
+        siv.add_global_callback('[', move |_| {
+            c_tx_clone.send(ControllerMessage::ChainStep(-1)).unwrap();
+        });
+
+        let c_tx_clone = c_tx.clone();
+        siv.add_global_callback(']', move |_| {
+            c_tx_clone.send(ControllerMessage::ChainStep(1)).unwrap();
+        });
+
+        // s / r: cycle the sort of the baskets / the rows in the reserves panel, S / R: reverse it,
+        // m: cycle the price mode, d: show only the baskets that changed in the last block
+        for (key, action) in [
+            ('s', Reserves::next_basket_sort as fn(&mut Reserves)),
+            ('S', Reserves::toggle_basket_order),
+            ('r', Reserves::next_row_sort),
+            ('R', Reserves::toggle_row_order),
+            ('m', Reserves::next_price_mode),
+            ('d', Reserves::toggle_changed_only),
+        ] {
+            siv.add_global_callback(key, move |s| {
+                s.call_on_name("RESERVES", |view: &mut Reserves| {
+                    action(view);
+                    view.update_view();
+                });
+            });
+        }
+
+        // M: choose the reserve to express the prices in
+        siv.add_global_callback('M', crate::menu::cross_reserve_dialog);
+
         crate::menu::set_menubar(&mut siv, c_tx.clone());
         siv.set_autohide_menu(false);
 
@@ -68,10 +122,27 @@ impl UI {
                         .full_width(),
                     )
                     .child(
-                        Panel::new(LogView::new(l_rx))
-                            .title("Log")
-                            .full_height()
-                            .max_height(20),
+                        Panel::new(
+                            LinearLayout::vertical()
+                                .child(
+                                    EditView::new()
+                                        .on_edit(|siv, query, _| {
+                                            siv.call_on_name("LOG", |log: &mut LogView| {
+                                                log.set_query(query)
+                                            });
+                                        })
+                                        .with_name("log_search")
+                                        .full_width(),
+                                )
+                                .child(
+                                    LogView::new(l_rx, log_settings, c_tx.clone())
+                                        .with_name("LOG")
+                                        .full_height(),
+                                ),
+                        )
+                        .title("Log")
+                        .full_height()
+                        .max_height(20),
                     ),
             )
             .child(
@@ -79,13 +150,54 @@ impl UI {
                     .title("Reserves")
                     .full_width(),
             )
-            .child(Panel::new(TVL::new().with_name("TVL")).title("TVL"));
+            .child(
+                LinearLayout::vertical()
+                    .child(Panel::new(TVL::new().with_name("TVL")).title("TVL"))
+                    .child(
+                        Panel::new(Opportunities::new().with_name("OPPORTUNITIES"))
+                            .title("Opportunities")
+                            .full_height(),
+                    ),
+            );
 
-        siv.add_fullscreen_layer(main_view);
+        siv.add_fullscreen_layer(
+            LinearLayout::vertical()
+                .child(ChainTabs::new().with_name("TABS"))
+                .child(main_view),
+        );
 
         UI { siv, ui_rx, ui_tx }
     }
 
+    /// Captures the current selection of currencies and baskets as a watchlist.
+    pub fn watchlist(&mut self, name: String, chain: String) -> Watchlist {
+        let currencies = self
+            .siv
+            .call_on_name("SELECTOR", |selector: &mut Selector| {
+                selector
+                    .checked_currencies()
+                    .into_iter()
+                    .map(|c| c.currencydefinition.fullyqualifiedname)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let (baskets, basket_mode) = self
+            .siv
+            .call_on_name("RESERVES", |reserves: &mut Reserves| {
+                (reserves.selected_baskets(), reserves.basket_mode())
+            })
+            .unwrap_or((vec![], BasketMode::All));
+
+        Watchlist {
+            name,
+            chain,
+            currencies,
+            baskets,
+            basket_mode,
+        }
+    }
+
     pub fn step(&mut self) -> bool {
         if !self.siv.is_running() {
             return false;
@@ -93,7 +205,7 @@ impl UI {
 
         while let Some(message) = self.ui_rx.try_iter().next() {
             match message {
-                UIMessage::UpdateReserveOverview(baskets) => {
+                UIMessage::UpdateReserveOverview(baskets, previous_baskets) => {
                     debug!("update reserve overview");
                     debug!("{:#?}", &baskets);
 
@@ -102,7 +214,7 @@ impl UI {
                         cb_sink
                             .send(Box::new(move |s| {
                                 s.call_on_name("RESERVES", |reserves_view: &mut Reserves| {
-                                    reserves_view.update_baskets(baskets);
+                                    reserves_view.update_baskets(baskets, previous_baskets);
 
                                     reserves_view.update_view();
                                 });
@@ -117,28 +229,44 @@ impl UI {
                             selector_view.update(vec);
                         });
                 }
-                UIMessage::UpdateTLV(hm) => {
+                UIMessage::UpdateTLV(hm, valuation) => {
                     let cb_sink = self.siv.cb_sink().clone();
                     cb_sink
                         .send(Box::new(|s| {
-                            s.call_on_name("TVL", |tvl_view: &mut TVL| tvl_view.update(hm));
+                            s.call_on_name("TVL", |tvl_view: &mut TVL| {
+                                tvl_view.update(hm, valuation)
+                            });
+                        }))
+                        .unwrap();
+                }
+                UIMessage::UpdateOpportunities(opportunities) => {
+                    let cb_sink = self.siv.cb_sink().clone();
+                    cb_sink
+                        .send(Box::new(|s| {
+                            s.call_on_name("OPPORTUNITIES", |view: &mut Opportunities| {
+                                view.update(opportunities)
+                            });
                         }))
                         .unwrap();
                 }
                 UIMessage::ApplyFilter => {
-                    let mut checked_currencies = vec![];
-
                     let cb_sink = self.siv.cb_sink().clone();
                     std::thread::spawn(move || {
                         cb_sink.send(Box::new(move |s| {
-                            s.call_on_all_named("filterbox", |filterbox: &mut FilterBox| {
-                                if filterbox.checkbox.is_checked() {
-                                    debug!("{}", &filterbox.currency.currencydefinition.name);
-                                    checked_currencies.push(filterbox.currency.clone());
-                                }
-                            });
+                            let checked_currencies = s
+                                .call_on_name("SELECTOR", |selector: &mut Selector| {
+                                    selector.checked_currencies()
+                                })
+                                .unwrap_or_default();
 
-                            // debug!("{:?}", &checked_currencies);
+                            s.call_on_name("LOG", |log: &mut LogView| {
+                                log.set_checked_currencies(
+                                    checked_currencies
+                                        .iter()
+                                        .map(|c| c.currencydefinition.fullyqualifiedname.clone())
+                                        .collect(),
+                                )
+                            });
 
                             s.call_on_name("RESERVES", |reserves_view: &mut Reserves| {
                                 reserves_view.update_checked_currencies(checked_currencies);
@@ -159,6 +287,128 @@ impl UI {
                         }))
                         .unwrap();
                 }
+                UIMessage::CrossChainDialog(comparisons) => {
+                    let cb_sink = self.siv.cb_sink().clone();
+                    cb_sink
+                        .send(Box::new(|s| {
+                            s.add_layer(
+                                Dialog::around(
+                                    CrossChain::new()
+                                        .with_name("CROSSCHAIN")
+                                        .min_width(60)
+                                        .max_height(30),
+                                )
+                                .title("Cross-chain prices")
+                                .dismiss_button("Close"),
+                            );
+                            s.call_on_name("CROSSCHAIN", |view: &mut CrossChain| {
+                                view.update(comparisons)
+                            });
+                        }))
+                        .unwrap();
+                }
+                UIMessage::UpdateCrossChain(comparisons) => {
+                    let cb_sink = self.siv.cb_sink().clone();
+                    cb_sink
+                        .send(Box::new(|s| {
+                            s.call_on_name("CROSSCHAIN", |view: &mut CrossChain| {
+                                view.update(comparisons)
+                            });
+                        }))
+                        .unwrap();
+                }
+                UIMessage::UpdateChainTabs(chains, active) => {
+                    self.siv.call_on_name("TABS", |tabs: &mut ChainTabs| {
+                        tabs.set_chains(chains, active.clone())
+                    });
+                    self.siv
+                        .call_on_name("LOG", |log: &mut LogView| log.set_chain(active));
+                }
+                UIMessage::ActiveChainChange(active) => {
+                    self.siv.call_on_name("TABS", |tabs: &mut ChainTabs| {
+                        tabs.set_active(active.clone())
+                    });
+                    self.siv
+                        .call_on_name("LOG", |log: &mut LogView| log.set_chain(active));
+                }
+                UIMessage::ChainUpdated(chain) => {
+                    self.siv
+                        .call_on_name("TABS", |tabs: &mut ChainTabs| tabs.mark_updated(chain));
+                }
+                UIMessage::BasketDialog(names) => {
+                    let cb_sink = self.siv.cb_sink().clone();
+                    cb_sink
+                        .send(Box::new(|s| {
+                            if s.find_name::<BasketDialog>("basket_dialog").is_some() {
+                                debug!("basket dialog found, ignore");
+                                return;
+                            }
+
+                            let selection = s
+                                .call_on_name("RESERVES", |view: &mut Reserves| {
+                                    view.selected_baskets()
+                                })
+                                .unwrap_or_default();
+
+                            s.add_layer(
+                                BasketDialog::new(names, selection).with_name("basket_dialog"),
+                            );
+                        }))
+                        .unwrap();
+                }
+                UIMessage::UpdateWatchlists(c_tx, names) => {
+                    crate::menu::set_watchlists(&mut self.siv, c_tx, names);
+                }
+                UIMessage::ApplyWatchlist(watchlist) => {
+                    debug!("apply watchlist {:?}", watchlist);
+
+                    let checked_currencies = self
+                        .siv
+                        .call_on_name("SELECTOR", |selector: &mut Selector| {
+                            selector.check_only(&watchlist.currencies);
+                            selector.checked_currencies()
+                        })
+                        .unwrap_or_default();
+
+                    self.siv.call_on_name("LOG", |log: &mut LogView| {
+                        log.set_checked_currencies(
+                            checked_currencies
+                                .iter()
+                                .map(|c| c.currencydefinition.fullyqualifiedname.clone())
+                                .collect(),
+                        )
+                    });
+
+                    self.siv
+                        .call_on_name("RESERVES", |reserves_view: &mut Reserves| {
+                            reserves_view.update_checked_currencies(checked_currencies);
+                            reserves_view.update_selected_baskets(watchlist.baskets);
+                            reserves_view.update_basket_mode(watchlist.basket_mode);
+
+                            reserves_view.update_view();
+                        });
+                }
+                UIMessage::TransactionDetails(txid, details) => {
+                    let cb_sink = self.siv.cb_sink().clone();
+                    cb_sink
+                        .send(Box::new(move |s| {
+                            s.add_layer(
+                                Dialog::around(ScrollView::new(TextView::new(details)))
+                                    .title(txid)
+                                    .dismiss_button("Close")
+                                    .max_height(40),
+                            );
+                        }))
+                        .unwrap();
+                }
+                UIMessage::ShowMessage(message) => {
+                    let cb_sink = self.siv.cb_sink().clone();
+                    cb_sink
+                        .send(Box::new(|s| {
+                            s.add_layer(Dialog::info(message));
+                        }))
+                        .unwrap();
+                }
                 UIMessage::BasketModeChange(basket_mode) => {
                     let cb_sink = self.siv.cb_sink().clone();
                     cb_sink
@@ -181,11 +431,24 @@ impl UI {
 }
 
 pub enum UIMessage {
-    UpdateReserveOverview(Vec<Basket>),
+    // the latest baskets, and the baskets in the block before
+    UpdateReserveOverview(Vec<Basket>, Vec<Basket>),
     UpdateSelectorCurrencies(Vec<Currency>),
-    UpdateTLV(BTreeMap<String, f64>),
+    UpdateTLV(Tvl, Option<Valuation>),
+    UpdateOpportunities(Vec<Opportunity>),
     ApplyFilter,
     NewLog(String),
     PBaasDialog(mpsc::Sender<ControllerMessage>, Vec<String>),
     BasketModeChange(BasketMode),
+    CrossChainDialog(Vec<PriceComparison>),
+    UpdateCrossChain(Vec<PriceComparison>),
+    UpdateChainTabs(Vec<String>, String),
+    ActiveChainChange(String),
+    ChainUpdated(String),
+    ShowMessage(String),
+    // txid, the decoded transaction
+    TransactionDetails(String, String),
+    BasketDialog(Vec<String>),
+    UpdateWatchlists(mpsc::Sender<ControllerMessage>, Vec<String>),
+    ApplyWatchlist(Watchlist),
 }
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use super::Basket;
+
+/// The fee a basket charges for a conversion from one of its reserves to another reserve.
+/// (a conversion from or to the basket currency itself costs half of this)
+/// The fee is set by the protocol and is the same for every basket; it is not part of a currency definition.
+pub const RESERVE_TO_RESERVE_FEE: f64 = 0.0005;
+
+#[derive(Debug, Clone)]
+pub struct Opportunity {
+    pub base: String,
+    pub quote: String,
+    // the basket in which `base` gets you the most `quote`
+    pub sell_basket: String,
+    pub sell_rate: f64,
+    // the basket in which `quote` gets you the most `base`
+    pub buy_basket: String,
+    pub buy_rate: f64,
+    /// The relative result of converting `base` to `quote` in `sell_basket` and back to `base` in `buy_basket`, after fees.
+    pub net_spread: f64,
+}
+
+/// Returns the amount of `quote` that 1 `base` is worth in the given basket, if the basket has both reserves.
+///
+/// `priceinreserve` is the price of 1 basket currency denominated in a reserve, so the cross rate
+/// is the ratio of the two reserve prices.
+pub fn cross_rate(basket: &Basket, base: &str, quote: &str) -> Option<f64> {
+    let price_of = |name: &str| {
+        basket
+            .currency_state
+            .reservecurrencies
+            .iter()
+            .find(|rc| basket.currencynames.get(&rc.currencyid).map(|n| n.as_str()) == Some(name))
+            .map(|rc| rc.priceinreserve.as_vrsc())
+    };
+
+    match (price_of(base), price_of(quote)) {
+        (Some(base_price), Some(quote_price)) if base_price > 0.0 && quote_price > 0.0 => {
+            Some(quote_price / base_price)
+        }
+        _ => None,
+    }
+}
+
+/// Collects, for every pair of reserve currencies, the implied cross rate in each basket that holds both.
+/// Pairs are ordered alphabetically (`base` < `quote`), so every pair is only listed once.
+pub fn cross_rates(baskets: &[Basket]) -> BTreeMap<(String, String), Vec<(String, f64)>> {
+    let mut rates: BTreeMap<(String, String), Vec<(String, f64)>> = BTreeMap::new();
+
+    for basket in baskets {
+        let mut names = basket
+            .currency_state
+            .reservecurrencies
+            .iter()
+            .filter_map(|rc| basket.currencynames.get(&rc.currencyid))
+            .cloned()
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+
+        for (i, base) in names.iter().enumerate() {
+            for quote in names.iter().skip(i + 1) {
+                if let Some(rate) = cross_rate(basket, base, quote) {
+                    rates
+                        .entry((base.clone(), quote.clone()))
+                        .or_default()
+                        .push((basket.name.clone(), rate));
+                }
+            }
+        }
+    }
+
+    rates
+}
+
+/// Finds the reserve pairs whose cross rate differs between baskets by more than the conversion fees of a round trip.
+pub fn find_opportunities(baskets: &[Basket]) -> Vec<Opportunity> {
+    let mut opportunities = vec![];
+
+    for ((base, quote), rates) in cross_rates(baskets) {
+        if rates.len() < 2 {
+            continue;
+        }
+
+        let highest = rates.iter().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        let lowest = rates.iter().min_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+
+        // base -> quote in the basket with the highest rate, quote -> base in the basket with the lowest rate.
+        let net_spread = highest.1 / lowest.1 * (1.0 - RESERVE_TO_RESERVE_FEE).powi(2) - 1.0;
+
+        if net_spread > 0.0 {
+            opportunities.push(Opportunity {
+                base,
+                quote,
+                sell_basket: highest.0.clone(),
+                sell_rate: highest.1,
+                buy_basket: lowest.0.clone(),
+                buy_rate: lowest.1,
+                net_spread,
+            });
+        }
+    }
+
+    opportunities.sort_by(|a, b| b.net_spread.total_cmp(&a.net_spread));
+
+    opportunities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verus::testing::basket;
+
+    #[test]
+    fn cross_rate_is_the_ratio_of_the_reserve_prices() {
+        let basket = basket(
+            "BasketA",
+            &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+        );
+
+        assert!((cross_rate(&basket, "VRSC", "DAI.vETH").unwrap() - 2.0).abs() < 1e-9);
+        assert!((cross_rate(&basket, "DAI.vETH", "VRSC").unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(cross_rate(&basket, "VRSC", "MKR.vETH"), None);
+    }
+
+    #[test]
+    fn cross_rates_list_every_pair_once() {
+        let baskets = [
+            basket(
+                "BasketA",
+                &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+            ),
+            basket(
+                "BasketB",
+                &[
+                    ("VRSC", 1000.0, 1.0),
+                    ("DAI.vETH", 2000.0, 2.0),
+                    ("MKR.vETH", 1.0, 0.001),
+                ],
+            ),
+        ];
+
+        let rates = cross_rates(&baskets);
+
+        assert_eq!(
+            rates.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                ("DAI.vETH".to_string(), "MKR.vETH".to_string()),
+                ("DAI.vETH".to_string(), "VRSC".to_string()),
+                ("MKR.vETH".to_string(), "VRSC".to_string()),
+            ]
+        );
+
+        let dai_vrsc = &rates[&("DAI.vETH".to_string(), "VRSC".to_string())];
+        assert_eq!(dai_vrsc.len(), 2);
+        assert!(dai_vrsc.iter().all(|(_, rate)| (rate - 0.5).abs() < 1e-9));
+
+        let mkr_vrsc = &rates[&("MKR.vETH".to_string(), "VRSC".to_string())];
+        assert_eq!(mkr_vrsc.len(), 1);
+        assert_eq!(mkr_vrsc[0].0, "BasketB");
+        assert!((mkr_vrsc[0].1 - 1000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn finds_a_spread_larger_than_the_fees() {
+        let baskets = [
+            basket(
+                "BasketA",
+                &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+            ),
+            basket(
+                "BasketB",
+                &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.1)],
+            ),
+        ];
+
+        let opportunities = find_opportunities(&baskets);
+
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        assert_eq!(opportunity.base, "DAI.vETH");
+        assert_eq!(opportunity.quote, "VRSC");
+        // 1 DAI.vETH gets 0.5 VRSC in BasketA, and 1 VRSC gets 2.05 DAI.vETH in BasketB
+        assert_eq!(opportunity.sell_basket, "BasketA");
+        assert_eq!(opportunity.buy_basket, "BasketB");
+        // 0.5 * 2.05, minus a reserve to reserve fee in both baskets
+        assert!((opportunity.net_spread - (1.025 * 0.9995 * 0.9995 - 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn ignores_a_spread_smaller_than_the_fees() {
+        let baskets = [
+            basket(
+                "BasketA",
+                &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+            ),
+            basket(
+                "BasketB",
+                &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.001)],
+            ),
+        ];
+
+        assert!(find_opportunities(&baskets).is_empty());
+    }
+}
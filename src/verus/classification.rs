@@ -0,0 +1,149 @@
+use bitflags::bitflags;
+use serde::Deserialize;
+
+bitflags! {
+    /// The `options` of a currency definition.
+    pub struct CurrencyOptions: u32 {
+        const FRACTIONAL = 0x01;
+        const ID_ISSUANCE = 0x02;
+        const ID_STAKING = 0x04;
+        const ID_REFERRALS = 0x08;
+        const ID_REFERRAL_REQUIRED = 0x10;
+        const TOKEN = 0x20;
+        const SINGLE_CURRENCY = 0x40;
+        const GATEWAY = 0x80;
+        const PBAAS = 0x100;
+        const GATEWAY_CONVERTER = 0x200;
+        const GATEWAY_NAME_CONTROLLER = 0x400;
+        const NFT_TOKEN = 0x800;
+        const NO_IDS = 0x1000;
+    }
+}
+
+/// What kind of currency a currency is, decided by its options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CurrencyClass {
+    // the converter of a bridge between two systems
+    Bridge,
+    // a fractional currency with reserves
+    Basket,
+    Nft,
+    // a PBaaS chain
+    Chain,
+    // a gateway to a non-PBaaS system, e.g. Ethereum
+    Gateway,
+    Token,
+    Other,
+}
+
+impl CurrencyClass {
+    /// Classifies a currency by its options; the first class that applies wins, in the order of the enum.
+    pub fn from_options(options: u32) -> Self {
+        let options = CurrencyOptions::from_bits_truncate(options);
+
+        if options.contains(CurrencyOptions::GATEWAY_CONVERTER) {
+            CurrencyClass::Bridge
+        } else if options.contains(CurrencyOptions::FRACTIONAL) {
+            CurrencyClass::Basket
+        } else if options.contains(CurrencyOptions::NFT_TOKEN) {
+            CurrencyClass::Nft
+        } else if options.contains(CurrencyOptions::PBAAS) {
+            CurrencyClass::Chain
+        } else if options.contains(CurrencyOptions::GATEWAY) {
+            CurrencyClass::Gateway
+        } else if options.contains(CurrencyOptions::TOKEN) {
+            CurrencyClass::Token
+        } else {
+            CurrencyClass::Other
+        }
+    }
+}
+
+/// Which classes of currencies are shown where.
+#[derive(Deserialize, Clone)]
+pub struct CurrencySettings {
+    /// The classes of currencies that can be checked in the selector.
+    #[serde(default = "default_selector_classes")]
+    pub selector: Vec<CurrencyClass>,
+    /// The classes of currencies that are shown as baskets in the reserves panel.
+    #[serde(default = "default_basket_classes")]
+    pub baskets: Vec<CurrencyClass>,
+}
+
+impl Default for CurrencySettings {
+    fn default() -> Self {
+        CurrencySettings {
+            selector: default_selector_classes(),
+            baskets: default_basket_classes(),
+        }
+    }
+}
+
+impl CurrencySettings {
+    pub fn in_selector(&self, options: u32) -> bool {
+        self.selector
+            .contains(&CurrencyClass::from_options(options))
+    }
+
+    pub fn is_basket(&self, options: u32) -> bool {
+        self.baskets.contains(&CurrencyClass::from_options(options))
+    }
+}
+
+fn default_selector_classes() -> Vec<CurrencyClass> {
+    vec![
+        CurrencyClass::Token,
+        CurrencyClass::Chain,
+        CurrencyClass::Gateway,
+    ]
+}
+
+fn default_basket_classes() -> Vec<CurrencyClass> {
+    vec![CurrencyClass::Basket, CurrencyClass::Bridge]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the option values that were matched exactly before currencies were classified by their flags
+    #[test]
+    fn classifies_the_previously_listed_options() {
+        assert_eq!(CurrencyClass::from_options(32), CurrencyClass::Token);
+        assert_eq!(CurrencyClass::from_options(34), CurrencyClass::Token);
+        assert_eq!(CurrencyClass::from_options(40), CurrencyClass::Token);
+        assert_eq!(CurrencyClass::from_options(264), CurrencyClass::Chain);
+        assert_eq!(CurrencyClass::from_options(136), CurrencyClass::Gateway);
+        assert_eq!(CurrencyClass::from_options(33), CurrencyClass::Basket);
+        assert_eq!(CurrencyClass::from_options(35), CurrencyClass::Basket);
+        assert_eq!(CurrencyClass::from_options(545), CurrencyClass::Bridge);
+    }
+
+    #[test]
+    fn default_settings_keep_the_previous_lists() {
+        let settings = CurrencySettings::default();
+
+        for options in [32, 34, 40, 136, 264] {
+            assert!(settings.in_selector(options), "{} in the selector", options);
+            assert!(!settings.is_basket(options), "{} is no basket", options);
+        }
+
+        for options in [33, 35, 545] {
+            assert!(settings.is_basket(options), "{} is a basket", options);
+            assert!(
+                !settings.in_selector(options),
+                "{} not in the selector",
+                options
+            );
+        }
+    }
+
+    #[test]
+    fn nfts_are_not_tokens() {
+        assert_eq!(
+            CurrencyClass::from_options(0x800 | 0x20),
+            CurrencyClass::Nft
+        );
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{arbitrage::cross_rates, Basket};
+
+#[derive(Debug, Clone)]
+pub struct PriceComparison {
+    pub base: String,
+    pub quote: String,
+    /// the average price of 1 `base` in `quote` over all the baskets on a chain, per chain name
+    pub prices: BTreeMap<String, f64>,
+    /// the relative difference between the highest and the lowest price
+    pub divergence: f64,
+}
+
+/// Puts the price of every reserve pair that is found on more than one chain side by side.
+pub fn compare_chains(chain_baskets: &HashMap<String, Vec<Basket>>) -> Vec<PriceComparison> {
+    let mut pairs: BTreeMap<(String, String), BTreeMap<String, f64>> = BTreeMap::new();
+
+    for (chain, baskets) in chain_baskets {
+        for (pair, rates) in cross_rates(baskets) {
+            let average = rates.iter().map(|(_, rate)| rate).sum::<f64>() / rates.len() as f64;
+
+            pairs
+                .entry(pair)
+                .or_default()
+                .insert(chain.clone(), average);
+        }
+    }
+
+    let mut comparisons = pairs
+        .into_iter()
+        .filter(|(_, prices)| prices.len() > 1)
+        .map(|((base, quote), prices)| {
+            let highest = prices.values().cloned().fold(f64::MIN, f64::max);
+            let lowest = prices.values().cloned().fold(f64::MAX, f64::min);
+
+            PriceComparison {
+                base,
+                quote,
+                prices,
+                divergence: highest / lowest - 1.0,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    comparisons.sort_by(|a, b| b.divergence.total_cmp(&a.divergence));
+
+    comparisons
+}
@@ -0,0 +1,226 @@
+use serde_json::Value;
+
+/// Something other than a conversion that happened on a chain.
+#[derive(Debug, Clone)]
+pub enum ChainEvent {
+    // a currency was defined, with the options that tell what class of currency it is
+    NewCurrency { name: String, options: u32 },
+    // an identity was registered or updated
+    Identity { name: String },
+    // a notarization of the chain with this currency id
+    Notarization { currencyid: String },
+}
+
+/// The events in a transaction, with the output they were found in.
+/// The transaction is read as a verbose `getrawtransaction` result.
+pub fn chain_events(tx: &Value) -> Vec<(usize, ChainEvent)> {
+    tx["vout"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(n, vout)| {
+            let script = &vout["scriptPubKey"];
+
+            if let Some(definition) = script.get("currencydefinition") {
+                let name = definition["fullyqualifiedname"]
+                    .as_str()
+                    .or_else(|| definition["name"].as_str())?;
+                let options = definition["options"].as_u64().unwrap_or(0) as u32;
+
+                Some((
+                    n,
+                    ChainEvent::NewCurrency {
+                        name: name.to_string(),
+                        options,
+                    },
+                ))
+            } else if let Some(identity) = script.get("identityprimary") {
+                Some((
+                    n,
+                    ChainEvent::Identity {
+                        name: identity["name"].as_str()?.to_string(),
+                    },
+                ))
+            } else if let Some(notarization) = script.get("pbaasnotarization") {
+                Some((
+                    n,
+                    ChainEvent::Notarization {
+                        currencyid: notarization["currencyid"].as_str()?.to_string(),
+                    },
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// A step in the launch of a currency.
+#[derive(Debug, Clone)]
+pub enum LaunchEvent {
+    // a currency was defined, it is in its preconversion phase until its start block
+    Defined { currencyid: String, name: String },
+    // the preconversion phase of a currency ended; it launched, or it failed and is refunded
+    Cleared { currencyid: String, launched: bool },
+}
+
+/// The launch events in a block, with the transaction and output they were found in.
+/// The block is read as a `getblock` result with verbosity 2.
+pub fn launch_events(block: &Value) -> Vec<(String, usize, LaunchEvent)> {
+    block["tx"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|tx| {
+            let txid = tx["txid"].as_str().unwrap_or_default().to_string();
+
+            tx["vout"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .filter_map(move |(n, vout)| {
+                    let script = &vout["scriptPubKey"];
+
+                    let event = if let Some(definition) = script.get("currencydefinition") {
+                        LaunchEvent::Defined {
+                            currencyid: definition["currencyid"].as_str()?.to_string(),
+                            name: definition["fullyqualifiedname"]
+                                .as_str()
+                                .or_else(|| definition["name"].as_str())?
+                                .to_string(),
+                        }
+                    } else if let Some(notarization) = script.get("pbaasnotarization") {
+                        if notarization["launchcleared"].as_bool() != Some(true) {
+                            return None;
+                        }
+
+                        LaunchEvent::Cleared {
+                            currencyid: notarization["currencyid"].as_str()?.to_string(),
+                            launched: notarization["launchconfirmed"].as_bool() == Some(true),
+                        }
+                    } else {
+                        return None;
+                    };
+
+                    Some((txid.clone(), n, event))
+                })
+        })
+        .collect()
+}
+
+/// The ids and fully qualified names of the currencies in a `listcurrencies` result.
+pub fn currencies(list: &Value) -> Vec<(String, String)> {
+    list.as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|currency| {
+            let definition = &currency["currencydefinition"];
+
+            Some((
+                definition["currencyid"].as_str()?.to_string(),
+                definition["fullyqualifiedname"].as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const BASKET: &str = "i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx";
+
+    fn definition(options: u64) -> Value {
+        json!({ "scriptPubKey": { "currencydefinition": {
+            "currencyid": BASKET,
+            "name": "Bridge",
+            "fullyqualifiedname": "Bridge.vETH",
+            "options": options,
+        } } })
+    }
+
+    fn notarization(launchcleared: bool, launchconfirmed: bool) -> Value {
+        json!({ "scriptPubKey": { "pbaasnotarization": {
+            "currencyid": BASKET,
+            "launchcleared": launchcleared,
+            "launchconfirmed": launchconfirmed,
+        } } })
+    }
+
+    #[test]
+    fn finds_the_events_in_a_transaction() {
+        let tx = json!({ "vout": [
+            { "scriptPubKey": { "addresses": ["RJzRtfzrFKD5gbJXn8Yy1hTRS3BAc8JE3x"] } },
+            definition(545),
+            { "scriptPubKey": { "identityprimary": { "name": "alice" } } },
+            notarization(false, false),
+        ] });
+
+        let events = chain_events(&tx);
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            (1, ChainEvent::NewCurrency { name, options: 545 }) if name == "Bridge.vETH"
+        ));
+        assert!(matches!(&events[1], (2, ChainEvent::Identity { name }) if name == "alice"));
+        assert!(matches!(
+            &events[2],
+            (3, ChainEvent::Notarization { currencyid }) if currencyid == BASKET
+        ));
+    }
+
+    #[test]
+    fn keeps_the_options_of_a_new_currency() {
+        let tx = json!({ "vout": [definition(32)] });
+
+        assert!(matches!(
+            &chain_events(&tx)[..],
+            [(0, ChainEvent::NewCurrency { options: 32, .. })]
+        ));
+    }
+
+    #[test]
+    fn a_launch_ends_when_it_is_cleared() {
+        let block = json!({ "tx": [
+            { "txid": "definition", "vout": [definition(33)] },
+            { "txid": "pending", "vout": [notarization(false, false)] },
+            { "txid": "launched", "vout": [notarization(true, true)] },
+            { "txid": "refunded", "vout": [notarization(true, false)] },
+        ] });
+
+        let events = launch_events(&block);
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            &events[0],
+            (txid, 0, LaunchEvent::Defined { currencyid, name })
+                if txid == "definition" && currencyid == BASKET && name == "Bridge.vETH"
+        ));
+        assert!(matches!(
+            &events[1],
+            (txid, 0, LaunchEvent::Cleared { launched: true, .. }) if txid == "launched"
+        ));
+        assert!(matches!(
+            &events[2],
+            (txid, 0, LaunchEvent::Cleared { launched: false, .. }) if txid == "refunded"
+        ));
+    }
+
+    #[test]
+    fn lists_the_currencies_by_their_full_name() {
+        let list = json!([
+            { "currencydefinition": { "currencyid": BASKET, "fullyqualifiedname": "Bridge.vETH" } },
+            { "currencydefinition": { "name": "incomplete" } },
+        ]);
+
+        assert_eq!(
+            currencies(&list),
+            vec![(BASKET.to_string(), "Bridge.vETH".to_string())]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+// A reserve transfer goes through three transactions:
+// the transfer itself, which has a `reservetransfer` output,
+// the export that bundles transfers by spending their outputs, which has a `crosschainexport` output,
+// and the import that settles the export on the destination, which has a `crosschainimport` output
+// that refers to the export by its txid.
+// The transactions are read as verbose `getrawtransaction` results.
+
+fn outputs(tx: &Value) -> impl Iterator<Item = &Value> {
+    tx["vout"].as_array().into_iter().flatten()
+}
+
+/// The address a reserve transfer in output `vout` pays to.
+pub fn destination(tx: &Value, vout: usize) -> Option<String> {
+    tx["vout"][vout]["scriptPubKey"]["reservetransfer"]["destination"]["address"]
+        .as_str()
+        .map(String::from)
+}
+
+/// The outputs (txid, vout) that an export bundles, if the transaction is an export.
+pub fn exported_transfers(tx: &Value) -> Vec<(String, usize)> {
+    if !outputs(tx).any(|vout| vout["scriptPubKey"].get("crosschainexport").is_some()) {
+        return vec![];
+    }
+
+    tx["vin"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|vin| {
+            Some((
+                vin["txid"].as_str()?.to_string(),
+                vin["vout"].as_u64()? as usize,
+            ))
+        })
+        .collect()
+}
+
+/// The txids of the exports that a transaction imports.
+pub fn imported_exports(tx: &Value) -> Vec<String> {
+    outputs(tx)
+        .filter_map(|vout| vout["scriptPubKey"]["crosschainimport"]["exporttxid"].as_str())
+        .map(String::from)
+        .collect()
+}
+
+/// The txid of the export that the import in output `vout` imports.
+pub fn imported_export(tx: &Value, vout: usize) -> Option<String> {
+    tx["vout"][vout]["scriptPubKey"]["crosschainimport"]["exporttxid"]
+        .as_str()
+        .map(String::from)
+}
+
+/// The amounts of a currency that a transaction pays to an address, one for every output that pays it,
+/// in the order of the outputs.
+/// Reserve currencies are found in the reserve outputs, the native currency of the chain in the output values.
+pub fn payouts(tx: &Value, address: &str, currencyid: &str, native_currencyid: &str) -> Vec<f64> {
+    outputs(tx)
+        .filter(|vout| {
+            vout["scriptPubKey"]["addresses"]
+                .as_array()
+                .map(|addresses| addresses.iter().any(|a| a.as_str() == Some(address)))
+                .unwrap_or(false)
+        })
+        .filter_map(|vout| {
+            if currencyid == native_currencyid {
+                vout["value"].as_f64()
+            } else {
+                vout["scriptPubKey"]["reserveoutput"]["currencyvalues"][currencyid].as_f64()
+            }
+        })
+        // a reserve output has a native value of 0
+        .filter(|amount| *amount > 0.0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const VRSC: &str = "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV";
+    const DAI: &str = "iGBs4DWztRNvNEJBt4mqHszLxfKTNHTkhM";
+    const ADDRESS: &str = "RJzRtfzrFKD5gbJXn8Yy1hTRS3BAc8JE3x";
+
+    fn transfer() -> Value {
+        json!({
+            "txid": "transfer",
+            "vout": [
+                { "value": 0.0, "scriptPubKey": { "reservetransfer": {
+                    "currencyvalues": { DAI: 10.0 },
+                    "destinationcurrencyid": VRSC,
+                    "destination": { "address": ADDRESS },
+                } } },
+                { "value": 5.0, "scriptPubKey": { "addresses": ["RChange"] } },
+            ]
+        })
+    }
+
+    fn export() -> Value {
+        json!({
+            "txid": "export",
+            "vin": [
+                { "txid": "transfer", "vout": 0 },
+                { "txid": "other", "vout": 2 },
+            ],
+            "vout": [{ "value": 0.0, "scriptPubKey": { "crosschainexport": {} } }]
+        })
+    }
+
+    fn import() -> Value {
+        json!({
+            "txid": "import",
+            "vout": [
+                { "value": 0.0, "scriptPubKey": { "crosschainimport": { "exporttxid": "export" } } },
+                { "value": 0.0, "scriptPubKey": {
+                    "addresses": [ADDRESS],
+                    "reserveoutput": { "currencyvalues": { DAI: 4.0 } },
+                } },
+                { "value": 2.5, "scriptPubKey": { "addresses": [ADDRESS] } },
+                { "value": 0.0, "scriptPubKey": {
+                    "addresses": [ADDRESS],
+                    "reserveoutput": { "currencyvalues": { DAI: 6.0 } },
+                } },
+            ]
+        })
+    }
+
+    #[test]
+    fn reads_the_destination_of_a_transfer() {
+        assert_eq!(destination(&transfer(), 0).as_deref(), Some(ADDRESS));
+        assert_eq!(destination(&transfer(), 1), None);
+    }
+
+    #[test]
+    fn an_export_bundles_the_outputs_it_spends() {
+        assert_eq!(
+            exported_transfers(&export()),
+            vec![("transfer".to_string(), 0), ("other".to_string(), 2)]
+        );
+        // a transaction without a crosschainexport output is no export
+        assert!(exported_transfers(&transfer()).is_empty());
+    }
+
+    #[test]
+    fn an_import_refers_to_its_export() {
+        assert_eq!(imported_exports(&import()), vec!["export".to_string()]);
+        assert_eq!(imported_export(&import(), 0).as_deref(), Some("export"));
+        assert_eq!(imported_export(&import(), 1), None);
+    }
+
+    #[test]
+    fn every_payout_is_listed_on_its_own() {
+        assert_eq!(payouts(&import(), ADDRESS, DAI, VRSC), vec![4.0, 6.0]);
+        assert_eq!(payouts(&import(), ADDRESS, VRSC, VRSC), vec![2.5]);
+        assert!(payouts(&import(), "RChange", DAI, VRSC).is_empty());
+    }
+}
+
+/// The ids of the currencies whose state the transactions in a block may have changed:
+/// the destinations of transfers and the baskets they convert through, the currencies that imported exports,
+/// and the currencies that were notarized.
+/// The block is read as a `getblock` result with verbosity 2.
+pub fn touched_currencies(block: &Value) -> HashSet<String> {
+    block["tx"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(outputs)
+        .flat_map(|vout| {
+            let script = &vout["scriptPubKey"];
+
+            [
+                &script["reservetransfer"]["destinationcurrencyid"],
+                &script["reservetransfer"]["via"],
+                &script["crosschainimport"]["importcurrencyid"],
+                &script["pbaasnotarization"]["currencyid"],
+            ]
+            .into_iter()
+            .filter_map(|id| id.as_str().map(String::from))
+            .chain(
+                script["pbaasnotarization"]["currencystates"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|state| state.as_object())
+                    .flat_map(|state| state.keys().cloned()),
+            )
+            .collect::<Vec<_>>()
+        })
+        .collect()
+}
@@ -1,19 +1,33 @@
+pub mod arbitrage;
+pub mod classification;
+pub mod crosschain;
+pub mod events;
+pub mod lifecycle;
 pub mod pbaas;
+pub mod tvl;
+pub mod valuation;
 pub mod vrsc;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc;
+use std::time::Instant;
 
 use tracing::{debug, error, info};
 use vrsc_rpc::json::vrsc::Address;
 use vrsc_rpc::json::Currency;
 use vrsc_rpc::{Client, RpcApi};
 
-use crate::controller::ControllerMessage;
+use crate::configuration::{read_name_cache, write_name_cache, NameCache};
+use crate::controller::{ControllerMessage, IdNames};
+
+use self::classification::{CurrencyClass, CurrencySettings};
+
+// the number of RPC calls that are made at the same time when refreshing baskets
+const RPC_PARALLELISM: usize = 8;
 
 pub trait Chain {
     fn get_name(&self) -> String;
@@ -122,24 +136,141 @@ pub trait Chain {
 
     fn currency_id_to_name(&mut self, currency_id: Address) -> String;
 
-    // options 33 and 97 are decentralized and can be minted 'at will'
-    fn get_latest_currencies(&self) -> Result<Vec<Currency>, ()> {
-        let currencies = self.client().list_currencies(None).unwrap();
+    fn id_names(&self) -> IdNames;
+
+    /// Fills the name cache with the names stored on disk and the names of all the currencies this chain knows,
+    /// so that ids only need a `getcurrency` when a currency is new.
+    fn warm_name_cache(&self) {
+        let chainid = match self.client().get_blockchain_info() {
+            Ok(info) => info.chainid.to_string(),
+            Err(e) => {
+                error!("getblockchaininfo failed: {:?}", e);
+                return;
+            }
+        };
+
+        let mut names = match read_name_cache(&self.get_name()) {
+            Some(cache) if cache.chainid == chainid => cache.names,
+            Some(_) => {
+                info!(
+                    "name cache of {} belongs to another chain, discarding",
+                    self.get_name()
+                );
+                HashMap::new()
+            }
+            None => HashMap::new(),
+        };
+
+        for launch_state in [None, Some("imported")] {
+            match self.client().list_currencies(launch_state) {
+                Ok(currencies) => names.extend(currencies.0.into_iter().map(|currency| {
+                    (
+                        currency.currencydefinition.currencyid.to_string(),
+                        currency.currencydefinition.fullyqualifiedname,
+                    )
+                })),
+                Err(e) => error!("listcurrencies failed: {:?}", e),
+            }
+        }
+
+        debug!(
+            "{} currency names cached for {}",
+            names.len(),
+            self.get_name()
+        );
+
+        if let Ok(mut write) = self.id_names().write() {
+            write.extend(names);
+        }
+
+        self.save_name_cache();
+    }
+
+    fn save_name_cache(&self) {
+        let chainid = match self.client().get_blockchain_info() {
+            Ok(info) => info.chainid.to_string(),
+            Err(e) => {
+                error!("getblockchaininfo failed: {:?}", e);
+                return;
+            }
+        };
+
+        if let Ok(read) = self.id_names().read() {
+            let cache = NameCache {
+                chainid,
+                names: read.clone(),
+            };
+
+            if let Err(e) = write_name_cache(&self.get_name(), &cache) {
+                error!(
+                    "could not save the name cache of {}: {:?}",
+                    self.get_name(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Looks up the names of the ids that are not in the name cache yet, `RPC_PARALLELISM` at a time.
+    fn cache_names(&self, ids: &[Address]) -> Result<(), ()> {
+        let missing: Vec<&Address> = match self.id_names().read() {
+            Ok(read) => ids
+                .iter()
+                .filter(|id| !read.contains_key(&id.to_string()))
+                .collect(),
+            Err(_) => return Err(()),
+        };
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let started = Instant::now();
+        let client = self.client();
+        let names = parallel_calls(&missing, |id| {
+            client
+                .get_currency(&id.to_string())
+                .map(|currency| (id.to_string(), currency.fullyqualifiedname))
+                .map_err(|e| format!("{:?}", e))
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| error!("getcurrency failed: {}", e))?;
+        debug!(
+            "fetched {} currency names in {:?}",
+            names.len(),
+            started.elapsed()
+        );
+
+        if let Ok(mut write) = self.id_names().write() {
+            write.extend(names);
+        }
+
+        Ok(())
+    }
+
+    /// The local and imported currencies of the classes that are shown in the selector.
+    fn get_latest_currencies(&self, settings: &CurrencySettings) -> Result<Vec<Currency>, ()> {
+        let currencies = self
+            .client()
+            .list_currencies(None)
+            .map_err(|e| error!("listcurrencies failed: {:?}", e))?;
 
         let mut filtered_currencies: Vec<Currency> = currencies
             .0
             .into_iter()
-            .filter(|currency| [32, 40, 264].contains(&currency.currencydefinition.options))
+            .filter(|currency| settings.in_selector(currency.currencydefinition.options as u32))
             .collect();
 
-        let currencies = self.client().list_currencies(Some("imported")).unwrap();
+        let currencies = self
+            .client()
+            .list_currencies(Some("imported"))
+            .map_err(|e| error!("listcurrencies failed: {:?}", e))?;
 
         let mut pbaas_currencies = currencies
             .0
             .into_iter()
-            .filter(|currency| {
-                [32, 34, 40, 136, 264].contains(&currency.currencydefinition.options)
-            })
+            .filter(|currency| settings.in_selector(currency.currencydefinition.options as u32))
             .collect();
 
         filtered_currencies.append(&mut pbaas_currencies);
@@ -151,9 +282,16 @@ pub trait Chain {
         Ok(filtered_currencies)
     }
 
-    fn get_latest_baskets(&mut self) -> Result<Vec<Basket>, ()> {
-        let currencies = self.client().list_currencies(None).unwrap();
-        let active_chain_id = self.client().get_blockchain_info().unwrap();
+    fn get_latest_baskets(&mut self, settings: &CurrencySettings) -> Result<Vec<Basket>, ()> {
+        let refresh_started = Instant::now();
+        let currencies = self
+            .client()
+            .list_currencies(None)
+            .map_err(|e| error!("listcurrencies failed: {:?}", e))?;
+        let active_chain_id = self
+            .client()
+            .get_blockchain_info()
+            .map_err(|e| error!("getblockchaininfo failed: {:?}", e))?;
 
         // A bridge has 2 sides, so we need to find out which sides in order to include the reserves in our baskets.
         // A bridge is defined on the subsystem and ties to the system it was launched from.
@@ -167,12 +305,27 @@ pub trait Chain {
             }
         };
 
+        // currency id -> (system id, launch system id, is a bridge), to describe the baskets
+        let mut definitions = HashMap::new();
+        let mut remember_definition = |currency: &Currency| {
+            definitions.insert(
+                currency.currencydefinition.currencyid.clone(),
+                (
+                    currency.currencydefinition.systemid.clone(),
+                    currency.currencydefinition.launchsystemid.clone(),
+                    CurrencyClass::from_options(currency.currencydefinition.options as u32)
+                        == CurrencyClass::Bridge,
+                ),
+            );
+        };
+
         let mut filtered_currencies: Vec<(String, Address)> = currencies
             .0
             .into_iter()
-            .filter(|currency| [33, 35, 545].contains(&currency.currencydefinition.options))
+            .filter(|currency| settings.is_basket(currency.currencydefinition.options as u32))
             .filter(active_chain_filter)
             .map(|currency| {
+                remember_definition(&currency);
                 (
                     currency.currencydefinition.fullyqualifiedname,
                     currency.currencydefinition.currencyid,
@@ -182,15 +335,25 @@ pub trait Chain {
 
         dbg!(&filtered_currencies);
 
-        let imported_currencies = self.client().list_currencies(Some("imported")).unwrap();
+        let imported_currencies = self
+            .client()
+            .list_currencies(Some("imported"))
+            .map_err(|e| error!("listcurrencies failed: {:?}", e))?;
 
         filtered_currencies.append(
             &mut imported_currencies
                 .0
                 .into_iter()
-                .filter(|currency| currency.currencydefinition.options == 545)
+                // of the imported baskets, only bridges have reserves on this chain
+                .filter(|currency| {
+                    let options = currency.currencydefinition.options as u32;
+
+                    settings.is_basket(options)
+                        && CurrencyClass::from_options(options) == CurrencyClass::Bridge
+                })
                 .filter(active_chain_filter)
                 .map(|currency| {
+                    remember_definition(&currency);
                     (
                         currency.currencydefinition.fullyqualifiedname,
                         currency.currencydefinition.currencyid,
@@ -204,15 +367,53 @@ pub trait Chain {
         filtered_currencies.sort_unstable();
         filtered_currencies.dedup();
 
+        let started = Instant::now();
+        let client = self.client();
+        let currency_states = parallel_calls(&filtered_currencies, |currency| {
+            client
+                .get_currency_state(&currency.1.to_string())
+                .map_err(|e| format!("{:?}", e))
+        });
+        debug!(
+            "fetched {} currency states in {:?}",
+            filtered_currencies.len(),
+            started.elapsed()
+        );
+
+        let currency_states = currency_states
+            .into_iter()
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(|e| error!("getcurrencystate failed: {}", e))?;
+
+        // the names that are not cached yet are looked up all at once, instead of one by one while the baskets are built
+        let mut ids: Vec<Address> = filtered_currencies
+            .iter()
+            .map(|currency| currency.1.clone())
+            .chain(
+                definitions
+                    .values()
+                    .flat_map(|(systemid, launchsystemid, _)| {
+                        std::iter::once(systemid.clone()).chain(launchsystemid.clone())
+                    }),
+            )
+            .chain(currency_states.iter().flat_map(|states| {
+                states.iter().flat_map(|state| {
+                    state
+                        .currencystate
+                        .reservecurrencies
+                        .iter()
+                        .map(|rc| rc.currencyid.clone())
+                })
+            }))
+            .collect();
+        ids.sort_unstable_by_key(|id| id.to_string());
+        ids.dedup();
+        self.cache_names(&ids)?;
+
         let mut last_currency_states = vec![];
 
-        for currency in &filtered_currencies {
-            if let Some(currency_state_result) = self
-                .client()
-                .get_currency_state(&currency.1.to_string())
-                .unwrap()
-                .first()
-            {
+        for (currency, currency_state) in filtered_currencies.iter().zip(currency_states.iter()) {
+            if let Some(currency_state_result) = currency_state.first() {
                 let currencynames = currency_state_result
                     .currencystate
                     .reservecurrencies
@@ -225,8 +426,16 @@ pub trait Chain {
                     })
                     .collect();
 
+                let (systemid, launchsystemid, bridge) = definitions
+                    .get(&currency.1)
+                    .cloned()
+                    .unwrap_or_else(|| (active_chain_id.chainid.clone(), None, false));
+
                 last_currency_states.push(Basket {
                     name: self.currency_id_to_name(currency.1.clone()),
+                    system: self.currency_id_to_name(systemid),
+                    launch_system: launchsystemid.map(|id| self.currency_id_to_name(id)),
+                    bridge,
                     currencyid: currency.1.clone(),
                     active_chain_id: active_chain_id.chainid.clone(),
                     currencynames,
@@ -236,9 +445,90 @@ pub trait Chain {
         }
 
         dbg!(&last_currency_states);
+        debug!(
+            "refreshed {} baskets of {} in {:?}",
+            last_currency_states.len(),
+            self.get_name(),
+            refresh_started.elapsed()
+        );
 
         Ok(last_currency_states)
     }
+
+    /// Fetches the latest state of the baskets whose id is in `touched`, and keeps the other baskets as they are.
+    fn refresh_baskets(
+        &self,
+        baskets: &[Basket],
+        touched: &HashSet<String>,
+    ) -> Result<Vec<Basket>, ()> {
+        let started = Instant::now();
+        let stale: Vec<&Basket> = baskets
+            .iter()
+            .filter(|basket| touched.contains(&basket.currencyid.to_string()))
+            .collect();
+
+        let client = self.client();
+        let currency_states = parallel_calls(&stale, |basket| {
+            client
+                .get_currency_state(&basket.currencyid.to_string())
+                .map_err(|e| format!("{:?}", e))
+        })
+        .into_iter()
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(|e| error!("getcurrencystate failed: {}", e))?;
+
+        let currency_states: HashMap<Address, _> = stale
+            .iter()
+            .map(|basket| basket.currencyid.clone())
+            .zip(currency_states)
+            .collect();
+
+        debug!(
+            "refreshed {} of {} baskets of {} in {:?}",
+            stale.len(),
+            baskets.len(),
+            self.get_name(),
+            started.elapsed()
+        );
+
+        Ok(baskets
+            .iter()
+            .cloned()
+            .map(|mut basket| {
+                if let Some(currency_state_result) = currency_states
+                    .get(&basket.currencyid)
+                    .and_then(|states| states.first())
+                {
+                    basket.currency_state = currency_state_result.currencystate.clone();
+                }
+
+                basket
+            })
+            .collect())
+    }
+}
+
+/// Runs `call` for every item on at most `RPC_PARALLELISM` threads, and returns the results in the order of the items.
+fn parallel_calls<T, R, F>(items: &[T], call: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let chunk_size = items.len().div_ceil(RPC_PARALLELISM).max(1);
+    let call = &call;
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(call).collect::<Vec<R>>()))
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
 }
 
 impl Debug for dyn Chain {
@@ -250,6 +540,12 @@ impl Debug for dyn Chain {
 #[derive(Debug, Clone)]
 pub struct Basket {
     pub name: String,
+    // the name of the chain the basket is defined on
+    pub system: String,
+    // the name of the chain the basket was launched from, if it was launched from another chain
+    pub launch_system: Option<String>,
+    // whether the basket is the converter of a bridge between two chains
+    pub bridge: bool,
     pub currencyid: Address,
     pub active_chain_id: Address,
     pub currency_state: vrsc_rpc::json::CurrencyState,
@@ -269,3 +565,85 @@ fn read_config_contents(path: &Path) -> HashMap<String, String> {
 
     map
 }
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::str::FromStr;
+
+    use serde_json::json;
+    use vrsc_rpc::json::vrsc::Address;
+
+    use super::Basket;
+
+    // names with valid currency ids, so that they can be read as addresses
+    const CURRENCIES: [(&str, &str); 10] = [
+        ("VRSC", "i5w5MuNik5NtLcYmNzcvaoixooEebB6MGV"),
+        ("vETH", "i9nwxtKuVYX4MSbeULLiK2ttVi6rUEhh4X"),
+        ("DAI.vETH", "iGBs4DWztRNvNEJBt4mqHszLxfKTNHTkhM"),
+        ("MKR.vETH", "iCkKJuJScy4Z6NSDK7Mt42ZAB2NEnAE1o4"),
+        ("tBTC.vETH", "iS8TfRPfVpKo5FVfSUzfHBQxo9KuzpnqLU"),
+        ("Bridge.vETH", "i3f7tSctFkiPpiedY8QR5Tep9p4qDVebDx"),
+        ("VRSCTEST", "iJhCezBExJHvtyH3fGhNnt2NhU4Ztkf2yq"),
+        ("BasketA", "i61cV2uicKSi1rSMQCBNQeSYC3UAi9GVzd"),
+        ("BasketB", "iHax5qYQGbcMGqJKKrPorpzUBX2oFFXGnY"),
+        ("BasketC", "iC5TQFrFXSYLQGkiZ8FYmZHFJzaRF5CYgE"),
+    ];
+
+    pub fn id(name: &str) -> Address {
+        let (_, id) = CURRENCIES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .expect("a test currency");
+
+        Address::from_str(id).unwrap()
+    }
+
+    /// A basket on VRSC with the given reserves, as (name, reserves, price of the basket in the reserve).
+    /// The currency state is read from the JSON that `getcurrencystate` returns.
+    pub fn basket(name: &str, reserves: &[(&str, f64, f64)]) -> Basket {
+        let weight = 1.0 / reserves.len() as f64;
+
+        let currency_state = json!({
+            "flags": 49,
+            "version": 1,
+            "currencyid": id(name).to_string(),
+            "reservecurrencies": reserves.iter().map(|(reserve, amount, price)| json!({
+                "currencyid": id(reserve).to_string(),
+                "weight": weight,
+                "reserves": amount,
+                "priceinreserve": price,
+            })).collect::<Vec<_>>(),
+            "initialsupply": 0.0,
+            "emitted": 0.0,
+            "supply": 1000.0,
+            "currencies": reserves.iter().map(|(reserve, _, price)| (id(reserve).to_string(), json!({
+                "reservein": 0.0,
+                "primarycurrencyin": 0.0,
+                "reserveout": 0.0,
+                "lastconversionprice": price,
+                "viaconversionprice": price,
+                "fees": 0.0,
+                "conversionfees": 0.0,
+                "priorweights": weight,
+            }))).collect::<serde_json::Map<_, _>>(),
+            "primarycurrencyfees": 0.0,
+            "primarycurrencyconversionfees": 0.0,
+            "primarycurrencyout": 0.0,
+            "preconvertedout": 0.0,
+        });
+
+        Basket {
+            name: name.to_string(),
+            system: "VRSC".to_string(),
+            launch_system: None,
+            bridge: false,
+            currencyid: id(name),
+            active_chain_id: id("VRSC"),
+            currency_state: serde_json::from_value(currency_state).expect("a currency state"),
+            currencynames: reserves
+                .iter()
+                .map(|(reserve, _, _)| (id(reserve), reserve.to_string()))
+                .collect(),
+        }
+    }
+}
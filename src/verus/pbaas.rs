@@ -1,5 +1,5 @@
 use os_info::Type as OSType;
-use std::{collections::HashMap, fs::ReadDir, path::PathBuf, rc::Rc};
+use std::{collections::HashMap, fs::ReadDir, path::PathBuf, rc::Rc, sync::RwLock};
 use tracing::{debug, instrument, warn};
 use vrsc_rpc::{json::vrsc::Address, Auth, Client, RpcApi};
 
@@ -59,6 +59,10 @@ impl Chain for PBaaSChain {
         &self.client
     }
 
+    fn id_names(&self) -> IdNames {
+        Rc::clone(&self.id_names)
+    }
+
     fn currency_id_to_name(&mut self, currency_id: Address) -> String {
         if let Ok(mut write) = self.id_names.write() {
             write
@@ -78,7 +82,7 @@ impl Chain for PBaaSChain {
 
 impl PBaaSChain {
     #[instrument]
-    pub fn new(testnet: bool, currencyidhex: String, id_names: IdNames) -> Self {
+    pub fn new(testnet: bool, currencyidhex: String) -> Self {
         dbg!(&currencyidhex);
         let client = Client::chain(testnet, &currencyidhex, Auth::ConfigFile).unwrap();
         // unwrap: we can unwrap this because a pbaas chain instance is only created when it is locally found.
@@ -88,7 +92,7 @@ impl PBaaSChain {
             name: None,
             currencyidhex,
             client,
-            id_names,
+            id_names: Rc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -138,17 +142,13 @@ fn pbaas_dir_location(testnet: bool) -> Option<PathBuf> {
 /// Some assumptions have been made:
 /// - the PBaaS directory has not been edited by a user. It assumes that all the directories that are found in PBaaS directory are
 /// PBaaS chains. No guarantees can be given about each directory being an actual PBaaS chain.
-pub fn local_pbaas_chains(testnet: bool, id_names: IdNames) -> Vec<PBaaSChain> {
+pub fn local_pbaas_chains(testnet: bool) -> Vec<PBaaSChain> {
     if let Some(entries) = pbaas_dir_entries(testnet) {
         entries
             .filter_map(|d| d.ok())
             .map(|dir| {
                 let currencyidhex = dir.file_name();
-                PBaaSChain::new(
-                    testnet,
-                    currencyidhex.to_string_lossy().to_string(),
-                    Rc::clone(&id_names),
-                )
+                PBaaSChain::new(testnet, currencyidhex.to_string_lossy().to_string())
             })
             .collect()
     } else {
@@ -0,0 +1,116 @@
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use super::Basket;
+
+/// The total value locked in the reserves of a set of baskets.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Tvl {
+    /// reserve currency name -> amount locked over all baskets
+    pub per_reserve: BTreeMap<String, f64>,
+    /// basket name -> reserve currency name -> amount locked in that basket
+    pub per_basket: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl Tvl {
+    /// Sums the reserves of the baskets, counting every basket only once.
+    ///
+    /// A bridge is visible from both of the chains it connects, so the same basket can occur twice
+    /// in a basket list; baskets are therefore deduplicated by their currency id.
+    pub fn from_baskets(baskets: &[Basket]) -> Self {
+        let mut seen = HashSet::new();
+        let mut tvl = Tvl::default();
+
+        for basket in baskets {
+            if !seen.insert(&basket.currencyid) {
+                continue;
+            }
+
+            let breakdown = tvl.per_basket.entry(basket.name.clone()).or_default();
+
+            for rc in basket.currency_state.reservecurrencies.iter() {
+                let name = basket
+                    .currencynames
+                    .get(&rc.currencyid)
+                    .cloned()
+                    .unwrap_or_else(|| rc.currencyid.to_string());
+                let amount = rc.reserves.as_vrsc();
+
+                *breakdown.entry(name.clone()).or_default() += amount;
+                *tvl.per_reserve.entry(name).or_default() += amount;
+            }
+        }
+
+        tvl
+    }
+
+    /// The baskets that hold a reserve currency, with the amount they hold.
+    pub fn baskets_holding(&self, currency: &str) -> Vec<(String, f64)> {
+        self.per_basket
+            .iter()
+            .filter_map(|(basket, reserves)| {
+                reserves
+                    .get(currency)
+                    .map(|amount| (basket.clone(), *amount))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verus::testing::basket;
+
+    fn baskets() -> Vec<Basket> {
+        vec![
+            basket(
+                "Bridge.vETH",
+                &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+            ),
+            basket("BasketA", &[("VRSC", 500.0, 1.0), ("MKR.vETH", 1.5, 0.01)]),
+        ]
+    }
+
+    #[test]
+    fn sums_the_reserves_of_all_baskets() {
+        let tvl = Tvl::from_baskets(&baskets());
+
+        assert_eq!(tvl.per_reserve["VRSC"], 1500.0);
+        assert_eq!(tvl.per_reserve["DAI.vETH"], 2000.0);
+        assert_eq!(tvl.per_reserve["MKR.vETH"], 1.5);
+        assert_eq!(tvl.per_basket.len(), 2);
+        assert_eq!(tvl.per_basket["BasketA"]["VRSC"], 500.0);
+    }
+
+    #[test]
+    fn counts_a_basket_only_once() {
+        let mut baskets = baskets();
+        // a bridge is listed by both chains it connects
+        baskets.push(baskets[0].clone());
+
+        let tvl = Tvl::from_baskets(&baskets);
+
+        assert_eq!(tvl.per_reserve["VRSC"], 1500.0);
+        assert_eq!(tvl.per_basket["Bridge.vETH"]["DAI.vETH"], 2000.0);
+    }
+
+    #[test]
+    fn lists_the_baskets_holding_a_reserve() {
+        let tvl = Tvl::from_baskets(&baskets());
+
+        assert_eq!(
+            tvl.baskets_holding("VRSC"),
+            vec![
+                ("BasketA".to_string(), 500.0),
+                ("Bridge.vETH".to_string(), 1000.0)
+            ]
+        );
+        assert_eq!(
+            tvl.baskets_holding("DAI.vETH"),
+            vec![("Bridge.vETH".to_string(), 2000.0)]
+        );
+        assert!(tvl.baskets_holding("tBTC.vETH").is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use super::Basket;
+
+/// The prices of currencies in a single quote currency.
+#[derive(Debug, Clone)]
+pub struct Valuation {
+    pub quote: String,
+    pub prices: HashMap<String, f64>,
+}
+
+impl Valuation {
+    /// Derives the price of every currency that can be reached from the quote currency through the baskets.
+    ///
+    /// Manually set prices (in the quote currency) are never overwritten and serve as extra anchors.
+    /// A basket that has a priced reserve gets a price itself, which in turn prices all of its other reserves.
+    /// When several baskets can price a currency, the basket with the deepest priced reserve wins.
+    pub fn derive(baskets: &[Basket], quote: &str, manual_prices: &HashMap<String, f64>) -> Self {
+        let mut prices = manual_prices.clone();
+        prices.insert(quote.to_string(), 1.0);
+
+        loop {
+            // currency name -> (price, depth of the reserve the price was derived from)
+            let mut candidates: HashMap<String, (f64, f64)> = HashMap::new();
+
+            for basket in baskets {
+                let reserves = basket
+                    .currency_state
+                    .reservecurrencies
+                    .iter()
+                    .filter_map(|rc| {
+                        basket
+                            .currencynames
+                            .get(&rc.currencyid)
+                            .map(|name| (name, rc.priceinreserve.as_vrsc(), rc.reserves.as_vrsc()))
+                    })
+                    .filter(|(_, price, _)| *price > 0.0)
+                    .collect::<Vec<_>>();
+
+                // the reserve with the most value that already has a price determines the price of the basket
+                let anchor = reserves
+                    .iter()
+                    .filter_map(|(name, price_in_reserve, amount)| {
+                        prices
+                            .get(*name)
+                            .map(|price| (price_in_reserve * price, amount * price))
+                    })
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+
+                if let Some((basket_price, depth)) = anchor {
+                    let mut propose = |name: &String, price: f64| {
+                        if !prices.contains_key(name) {
+                            let candidate =
+                                candidates.entry(name.clone()).or_insert((price, depth));
+                            if depth > candidate.1 {
+                                *candidate = (price, depth);
+                            }
+                        }
+                    };
+
+                    propose(&basket.name, basket_price);
+
+                    for (name, price_in_reserve, _) in reserves.iter() {
+                        propose(name, basket_price / price_in_reserve);
+                    }
+                }
+            }
+
+            if candidates.is_empty() {
+                break;
+            }
+
+            prices.extend(
+                candidates
+                    .into_iter()
+                    .map(|(name, (price, _))| (name, price)),
+            );
+        }
+
+        Valuation {
+            quote: quote.to_string(),
+            prices,
+        }
+    }
+
+    pub fn value_of(&self, currency: &str, amount: f64) -> Option<f64> {
+        self.prices.get(currency).map(|price| price * amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verus::testing::basket;
+
+    fn close(price: Option<&f64>, expected: f64) -> bool {
+        price.map(|price| (price - expected).abs() < 1e-9) == Some(true)
+    }
+
+    #[test]
+    fn prices_the_basket_and_its_reserves() {
+        let baskets = [basket(
+            "Bridge.vETH",
+            &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+        )];
+
+        let valuation = Valuation::derive(&baskets, "VRSC", &HashMap::new());
+
+        assert!(close(valuation.prices.get("VRSC"), 1.0));
+        assert!(close(valuation.prices.get("Bridge.vETH"), 2.0));
+        assert!(close(valuation.prices.get("DAI.vETH"), 0.5));
+        assert!(close(valuation.value_of("DAI.vETH", 10.0).as_ref(), 5.0));
+    }
+
+    #[test]
+    fn prices_through_several_baskets() {
+        let baskets = [
+            basket(
+                "BasketA",
+                &[("VRSC", 1000.0, 1.0), ("DAI.vETH", 1000.0, 2.0)],
+            ),
+            basket(
+                "BasketB",
+                &[("DAI.vETH", 1000.0, 1.0), ("MKR.vETH", 1.0, 0.001)],
+            ),
+        ];
+
+        let valuation = Valuation::derive(&baskets, "VRSC", &HashMap::new());
+
+        // 1 DAI.vETH is 0.5 VRSC, BasketB is 1 DAI.vETH and 1 MKR.vETH is 1000 BasketB
+        assert!(close(valuation.prices.get("BasketB"), 0.5));
+        assert!(close(valuation.prices.get("MKR.vETH"), 500.0));
+    }
+
+    #[test]
+    fn the_deepest_reserve_sets_the_price() {
+        let baskets = [
+            basket("BasketA", &[("VRSC", 100.0, 1.0), ("MKR.vETH", 1.0, 0.5)]),
+            basket(
+                "BasketB",
+                &[("VRSC", 10000.0, 1.0), ("MKR.vETH", 1.0, 0.25)],
+            ),
+        ];
+
+        let valuation = Valuation::derive(&baskets, "VRSC", &HashMap::new());
+
+        assert!(close(valuation.prices.get("MKR.vETH"), 4.0));
+    }
+
+    #[test]
+    fn keeps_manual_prices() {
+        let baskets = [basket(
+            "Bridge.vETH",
+            &[("VRSC", 1000.0, 2.0), ("DAI.vETH", 2000.0, 4.0)],
+        )];
+        let manual_prices = HashMap::from([("DAI.vETH".to_string(), 0.6)]);
+
+        let valuation = Valuation::derive(&baskets, "VRSC", &manual_prices);
+
+        assert!(close(valuation.prices.get("DAI.vETH"), 0.6));
+    }
+
+    #[test]
+    fn leaves_unreachable_currencies_unpriced() {
+        let baskets = [basket(
+            "BasketA",
+            &[("tBTC.vETH", 1.0, 0.001), ("MKR.vETH", 1.0, 0.01)],
+        )];
+
+        let valuation = Valuation::derive(&baskets, "VRSC", &HashMap::new());
+
+        assert_eq!(valuation.prices.len(), 1);
+        assert_eq!(valuation.value_of("MKR.vETH", 1.0), None);
+    }
+}
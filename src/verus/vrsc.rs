@@ -1,5 +1,5 @@
 use os_info::Type as OSType;
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, path::PathBuf, rc::Rc, sync::RwLock};
 use vrsc_rpc::{Client, RpcApi};
 
 use crate::controller::IdNames;
@@ -33,6 +33,10 @@ impl Chain for VerusChain {
         &self.client
     }
 
+    fn id_names(&self) -> IdNames {
+        Rc::clone(&self.id_names)
+    }
+
     fn currency_id_to_name(&mut self, currency_id: vrsc_rpc::json::vrsc::Address) -> String {
         if let Ok(mut write) = self.id_names.write() {
             write
@@ -86,7 +90,7 @@ impl Chain for VerusChain {
 }
 
 impl VerusChain {
-    pub fn new(testnet: bool, id_names: IdNames) -> Self {
+    pub fn new(testnet: bool) -> Self {
         let client = Client::vrsc(testnet, vrsc_rpc::Auth::ConfigFile).unwrap();
 
         if testnet {
@@ -95,7 +99,7 @@ impl VerusChain {
                 name: "vrsctest".to_string(),
                 currencyidhex: "2d4eb6919e9fdb2934ff2481325e6335a29eefa6".to_string(),
                 client,
-                id_names,
+                id_names: Rc::new(RwLock::new(HashMap::new())),
             }
         } else {
             VerusChain {
@@ -103,7 +107,7 @@ impl VerusChain {
                 name: "VRSC".to_string(),
                 currencyidhex: "4c6c9b5a9f7f31d8ea604cb49ad3645c01b8f51a".to_string(),
                 client,
-                id_names,
+                id_names: Rc::new(RwLock::new(HashMap::new())),
             }
         }
     }
@@ -0,0 +1,144 @@
+use std::{cell::RefCell, collections::BTreeSet, rc::Rc};
+
+use cursive::{
+    view::{Nameable, Resizable, ViewWrapper},
+    views::*,
+    wrap_impl, Cursive,
+};
+use tracing::debug;
+
+use crate::{menu::BasketMode, views::reserves::Reserves};
+
+/// Lets the user pick baskets by name. Besides the checked names, a pattern (`*` matches anything, `?` a single character)
+/// can be given, so that baskets that are defined later on are selected as well.
+pub struct BasketDialog {
+    view: ResizedView<Dialog>,
+}
+
+impl BasketDialog {
+    pub fn new(names: Vec<String>, selection: Vec<String>) -> Self {
+        // the checked names; a selection entry that is not a basket name is a pattern
+        let checked = Rc::new(RefCell::new(
+            selection
+                .iter()
+                .filter(|s| names.contains(s))
+                .cloned()
+                .collect::<BTreeSet<String>>(),
+        ));
+        let pattern = selection
+            .iter()
+            .find(|s| !names.contains(s))
+            .cloned()
+            .unwrap_or_default();
+
+        let mut list = LinearLayout::vertical();
+        for name in names.iter() {
+            let checked_clone = Rc::clone(&checked);
+            let name_clone = name.clone();
+
+            list.add_child(
+                LinearLayout::horizontal()
+                    .child(
+                        Checkbox::new()
+                            .with_checked(checked.borrow().contains(name))
+                            .on_change(move |_, is_checked| {
+                                if is_checked {
+                                    checked_clone.borrow_mut().insert(name_clone.clone());
+                                } else {
+                                    checked_clone.borrow_mut().remove(&name_clone);
+                                }
+                            })
+                            .with_name(checkbox_name(name)),
+                    )
+                    .child(TextView::new(format!(" {}", name))),
+            );
+        }
+
+        let checked_clone = Rc::clone(&checked);
+        let select_matching = move |siv: &mut Cursive| {
+            let pattern = siv
+                .call_on_name("basket_pattern", |edit: &mut EditView| edit.get_content())
+                .unwrap_or_default();
+
+            for name in names.iter().filter(|name| matches_pattern(&pattern, name)) {
+                checked_clone.borrow_mut().insert(name.clone());
+                siv.call_on_name(&checkbox_name(name), |checkbox: &mut Checkbox| {
+                    let _ = checkbox.check();
+                });
+            }
+        };
+
+        let apply = move |siv: &mut Cursive| {
+            let mut selection = checked.borrow().iter().cloned().collect::<Vec<_>>();
+
+            if let Some(pattern) =
+                siv.call_on_name("basket_pattern", |edit: &mut EditView| edit.get_content())
+            {
+                if !pattern.is_empty() {
+                    selection.push(pattern.to_string());
+                }
+            }
+
+            debug!("selected baskets: {:?}", selection);
+
+            siv.call_on_name("RESERVES", |view: &mut Reserves| {
+                view.update_selected_baskets(selection);
+                view.update_basket_mode(BasketMode::Baskets);
+
+                view.update_view();
+            });
+            siv.pop_layer();
+        };
+
+        let view = Dialog::around(
+            LinearLayout::vertical()
+                .child(
+                    LinearLayout::horizontal()
+                        .child(TextView::new("Pattern: "))
+                        .child(
+                            EditView::new()
+                                .content(pattern)
+                                .with_name("basket_pattern")
+                                .full_width(),
+                        ),
+                )
+                .child(ScrollView::new(list).max_height(20)),
+        )
+        .title("Select baskets")
+        .button("Select matching", select_matching)
+        .button("Apply", apply)
+        .dismiss_button("Cancel")
+        .min_width(50);
+
+        BasketDialog { view }
+    }
+}
+
+impl ViewWrapper for BasketDialog {
+    wrap_impl!(self.view: ResizedView<Dialog>);
+}
+
+fn checkbox_name(basket: &str) -> String {
+    format!("basket_checkbox_{}", basket)
+}
+
+/// Matches a basket name against a pattern in which `*` matches any number of characters and `?` exactly one.
+/// The match is case insensitive.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.first(), name.first()) {
+            (None, None) => true,
+            (Some('*'), _) => {
+                matches(&pattern[1..], name) || (!name.is_empty() && matches(pattern, &name[1..]))
+            }
+            (Some('?'), Some(_)) => matches(&pattern[1..], &name[1..]),
+            (Some(p), Some(n)) if p == n => matches(&pattern[1..], &name[1..]),
+            _ => false,
+        }
+    }
+
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let name = name.to_lowercase().chars().collect::<Vec<_>>();
+
+    matches(&pattern, &name)
+}
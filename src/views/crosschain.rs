@@ -0,0 +1,71 @@
+use cursive::{
+    theme::{BaseColor, Color},
+    utils::markup::StyledString,
+    view::ViewWrapper,
+    views::*,
+    View,
+};
+use tracing::debug;
+
+use crate::verus::crosschain::PriceComparison;
+
+/// Divergences above this fraction are highlighted.
+const DIVERGENCE_THRESHOLD: f64 = 0.01;
+
+pub struct CrossChain {
+    view: ScrollView<TextView>,
+}
+
+impl CrossChain {
+    pub fn new() -> impl View {
+        Self {
+            view: ScrollView::new(TextView::new(" no currencies found on more than one chain")),
+        }
+    }
+
+    pub fn update(&mut self, comparisons: Vec<PriceComparison>) {
+        debug!("update cross-chain prices: {:#?}", comparisons);
+
+        if comparisons.is_empty() {
+            self.view
+                .get_inner_mut()
+                .set_content(" no currencies found on more than one chain");
+
+            return;
+        }
+
+        let mut content = StyledString::new();
+
+        for comparison in comparisons.iter() {
+            let line = format!(
+                " {}/{}: {:.2}% divergence\n",
+                comparison.base,
+                comparison.quote,
+                comparison.divergence * 100.0
+            );
+
+            if comparison.divergence > DIVERGENCE_THRESHOLD {
+                content.append_styled(line, Color::Dark(BaseColor::Red));
+            } else {
+                content.append_plain(line);
+            }
+
+            for (chain, price) in comparison.prices.iter() {
+                content.append_plain(format!(
+                    "   {:<max_name_len$}: {value:>max$.*}\n",
+                    chain,
+                    8,
+                    max_name_len = 17,
+                    value = price,
+                    max = 17
+                ));
+            }
+        }
+
+        self.view.get_inner_mut().set_content(content);
+    }
+}
+
+impl ViewWrapper for CrossChain {
+    cursive::wrap_impl!(self.view: ScrollView<TextView>);
+}
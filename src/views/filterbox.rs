@@ -5,19 +5,30 @@ use vrsc_rpc::json::Currency;
 
 use crate::controller::ControllerMessage;
 
+use super::selector::Selector;
+
 pub struct FilterBox {
     pub currency: Currency,
     pub checkbox: Checkbox,
 }
 
 impl FilterBox {
-    pub fn new(currency: Currency, c_tx: Sender<ControllerMessage>) -> Self {
+    pub fn new(currency: Currency, checked: bool, c_tx: Sender<ControllerMessage>) -> Self {
+        let currencyid = currency.currencydefinition.currencyid.clone();
+
         FilterBox {
             currency,
-            checkbox: Checkbox::new().on_change(move |_, _| {
-                c_tx.send(ControllerMessage::CurrencySelectionChange)
-                    .unwrap();
-            }),
+            checkbox: Checkbox::new()
+                .with_checked(checked)
+                .on_change(move |siv, checked| {
+                    // the selector remembers the checked currencies, also when they are filtered out of view
+                    siv.call_on_name("SELECTOR", |selector: &mut Selector| {
+                        selector.set_checked(&currencyid, checked)
+                    });
+
+                    c_tx.send(ControllerMessage::CurrencySelectionChange)
+                        .unwrap();
+                }),
         }
     }
 }
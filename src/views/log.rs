@@ -1,29 +1,175 @@
 use std::{collections::VecDeque, sync::mpsc};
 
-use cursive::{Printer, Vec2, View};
+use cursive::{
+    direction::Direction,
+    event::{Callback, Event, EventResult, Key},
+    theme::ColorStyle,
+    view::CannotFocus,
+    views::EditView,
+    Printer, Vec2, View,
+};
 use vrsc_rpc::json::vrsc::Amount;
 
+use crate::{configuration::LogSettings, controller::ControllerMessage};
+
+/// Confirmations of a transfer are followed up to this number, so a higher minimum is capped to it.
+pub const MAX_CONFIRMATIONS: u64 = 10;
+
 pub struct LogView {
     buffer: VecDeque<LogMessage>,
     rx: mpsc::Receiver<LogMessage>,
+    // messages of all chains are kept, but only those of the active chain are shown
+    chain: String,
+    c_tx: mpsc::Sender<ControllerMessage>,
+    // the txid and output of the selected message; the newest message when none is selected
+    selected: Option<(String, usize)>,
+    // lowercase, matched against currency names and txids
+    query: String,
+    // only show the messages about the currencies that are checked in the selector
+    filter_by_selection: bool,
+    // fully qualified names of the checked currencies
+    checked_currencies: Vec<String>,
+    min_confirmations: u64,
 }
 
 impl LogView {
-    pub fn new(rx: mpsc::Receiver<LogMessage>) -> Self {
+    pub fn new(
+        rx: mpsc::Receiver<LogMessage>,
+        settings: LogSettings,
+        c_tx: mpsc::Sender<ControllerMessage>,
+    ) -> Self {
         let buffer = VecDeque::new();
 
-        LogView { buffer, rx }
+        LogView {
+            buffer,
+            rx,
+            chain: String::new(),
+            c_tx,
+            selected: None,
+            query: String::new(),
+            filter_by_selection: settings.filter_by_selection,
+            checked_currencies: vec![],
+            min_confirmations: settings.min_confirmations.min(MAX_CONFIRMATIONS),
+        }
+    }
+
+    pub fn set_chain(&mut self, chain: String) {
+        self.chain = chain;
+    }
+
+    pub fn set_checked_currencies(&mut self, checked_currencies: Vec<String>) {
+        self.checked_currencies = checked_currencies;
+    }
+
+    pub fn toggle_filter_by_selection(&mut self) {
+        self.filter_by_selection = !self.filter_by_selection;
+    }
+
+    pub fn min_confirmations(&self) -> u64 {
+        self.min_confirmations
+    }
+
+    pub fn set_min_confirmations(&mut self, min_confirmations: u64) {
+        self.min_confirmations = min_confirmations.min(MAX_CONFIRMATIONS);
     }
 
     fn update(&mut self) {
-        // Add each available line to the end of the buffer.
+        // Add each available line to the end of the buffer, or replace the message about the same transfer.
         while let Ok(message) = self.rx.try_recv() {
-            self.buffer.push_back(message);
+            upsert(&mut self.buffer, message);
+        }
+    }
 
-            if self.buffer.len() > 500 {
-                self.buffer.pop_front();
-            }
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_lowercase();
+    }
+
+    fn shows(&self, message: &LogMessage) -> bool {
+        message.chain == self.chain
+            && (self.query.is_empty() || message.matches(&self.query))
+            && message.confirmations >= self.min_confirmations
+            && (!self.filter_by_selection
+                || self.checked_currencies.contains(&message.reserve)
+                || self
+                    .checked_currencies
+                    .contains(&message.amount_in_currency))
+    }
+
+    // the shown messages, newest first
+    fn shown(&self) -> Vec<&LogMessage> {
+        self.buffer
+            .iter()
+            .rev()
+            .filter(|message| self.shows(message))
+            .collect()
+    }
+
+    // the position of the selected message among the shown messages, newest first
+    fn selected_index(&self, shown: &[&LogMessage]) -> usize {
+        self.selected
+            .as_ref()
+            .and_then(|(txid, vout)| {
+                shown
+                    .iter()
+                    .position(|message| &message.txid == txid && message.vout == *vout)
+            })
+            .unwrap_or(0)
+    }
+
+    // moves the selection to older (positive) or newer (negative) messages
+    fn move_selection(&mut self, step: isize) -> EventResult {
+        let shown = self.shown();
+        if shown.is_empty() {
+            return EventResult::Ignored;
+        }
+
+        let index = (self.selected_index(&shown) as isize + step).clamp(0, shown.len() as isize - 1)
+            as usize;
+        self.selected = Some((shown[index].txid.clone(), shown[index].vout));
+
+        EventResult::Consumed(None)
+    }
+
+    fn show_details(&self) -> EventResult {
+        let shown = self.shown();
+
+        // events that are not in a transaction, like the end of a preconversion, have no details to show
+        if let Some(message) = shown
+            .get(self.selected_index(&shown))
+            .filter(|message| !message.txid.is_empty())
+        {
+            let _ = self.c_tx.send(ControllerMessage::TransactionDetails(
+                message.chain.clone(),
+                message.txid.clone(),
+            ));
         }
+
+        EventResult::Consumed(None)
+    }
+}
+
+/// Adds a message to the end of a log, or replaces the earlier message about the same transfer, keeping its time.
+/// A transfer never goes back in its lifecycle, e.g. when it is mined after its export was already seen.
+/// The log is capped at 500 messages.
+pub fn upsert(log: &mut VecDeque<LogMessage>, mut message: LogMessage) {
+    if let Some(existing) = log.iter_mut().find(|m| m.is_same_transfer(&message)) {
+        message.time = existing.time.clone();
+
+        if existing.state > message.state {
+            message.state = existing.state;
+            message.export_txid = existing.export_txid.clone();
+            message.import_txid = existing.import_txid.clone();
+            message.amount_out = existing.amount_out;
+        }
+
+        *existing = message;
+        return;
+    }
+
+    log.push_back(message);
+
+    if log.len() > 500 {
+        log.pop_front();
     }
 }
 
@@ -33,42 +179,80 @@ impl View for LogView {
     }
 
     fn draw(&self, printer: &Printer) {
+        let shown = self.shown();
+        let selected = self.selected_index(&shown);
+
+        // skip as many of the newest messages as needed to keep the selected message in view
+        let mut skip = 0;
+        while skip < selected
+            && shown[skip..=selected]
+                .iter()
+                .map(|message| message.height())
+                .sum::<usize>()
+                > printer.size.y
+        {
+            skip += 1;
+        }
+
         let mut counter = 0;
-        for message in self.buffer.iter().rev() {
+        for (index, message) in shown.iter().enumerate().skip(skip) {
             counter += message.height();
 
-            let mut linenum = 1;
-            if let Some(amount_out) = message.amount_out {
-                printer.print(
-                    (0, printer.size.y - 1 - linenum - counter + message.height()),
-                    &format!("{}", amount_out),
-                );
-                linenum += 1;
+            // the oldest messages that do not fit anymore are not drawn
+            if counter > printer.size.y {
+                break;
+            }
+
+            let top = printer.size.y - counter;
+            printer.print((0, top), "-------------------");
+
+            for (i, line) in message.lines().iter().enumerate() {
+                let y = top + 1 + i;
+
+                // the first line of the selected message is highlighted
+                if i == 0 && index == selected {
+                    let style = if printer.focused {
+                        ColorStyle::highlight()
+                    } else {
+                        ColorStyle::highlight_inactive()
+                    };
+                    printer.with_color(style, |printer| printer.print((0, y), line));
+                } else {
+                    printer.print((0, y), line);
+                }
             }
+        }
+    }
+
+    fn take_focus(&mut self, _source: Direction) -> Result<EventResult, CannotFocus> {
+        Ok(EventResult::Consumed(None))
+    }
 
-            printer.print(
-                (0, printer.size.y - 1 - linenum - counter + message.height()),
-                &format!(
-                    "{} {}",
-                    message.amount_in.as_vrsc(),
-                    message.amount_in_currency
-                ),
-            );
-            linenum += 1;
-
-            printer.print(
-                (0, printer.size.y - 1 - linenum - counter + message.height()),
-                &format!(
-                    "{} transfer initiated for {} ",
-                    message.time, message.reserve
-                ),
-            );
-
-            linenum += 1;
-            printer.print(
-                (0, printer.size.y - 1 - linenum - counter + message.height()),
-                "-------------------",
-            )
+    // up / down: select an older / newer message, enter: show the transaction, /: search, esc: clear the search
+    fn on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Up) => self.move_selection(1),
+            Event::Key(Key::Down) => self.move_selection(-1),
+            Event::Key(Key::PageUp) => self.move_selection(5),
+            Event::Key(Key::PageDown) => self.move_selection(-5),
+            Event::Key(Key::Home) => self.move_selection(self.buffer.len() as isize),
+            Event::Key(Key::End) => {
+                self.selected = None;
+                EventResult::Consumed(None)
+            }
+            Event::Key(Key::Enter) => self.show_details(),
+            Event::Char('/') => EventResult::Consumed(Some(Callback::from_fn(|siv| {
+                let _ = siv.focus_name("log_search");
+            }))),
+            Event::Key(Key::Esc) if !self.query.is_empty() => {
+                self.query.clear();
+                EventResult::Consumed(Some(Callback::from_fn(|siv| {
+                    siv.call_on_name("log_search", |search: &mut EditView| {
+                        let _ = search.set_content("");
+                    });
+                })))
+            }
+            _ => EventResult::Ignored,
         }
     }
 }
@@ -76,25 +260,137 @@ impl View for LogView {
 #[derive(Clone)]
 pub struct LogMessage {
     pub time: String,
+    pub chain: String,
     pub _type: MessageType,
     pub reserve: String,
     pub amount_in_currency: String,
     pub amount_in: Amount,
     pub amount_out: Option<f64>,
+    pub txid: String,
+    // the index of the output of the transaction that this message is about
+    pub vout: usize,
+    // 0 while the transaction is in the mempool
+    pub confirmations: u64,
+    pub state: TransferState,
+    // the address the transfer pays to, to find the amount it settled for
+    pub destination: Option<String>,
+    // the currency the transfer pays out in
+    pub destination_currencyid: String,
+    pub export_txid: Option<String>,
+    pub import_txid: Option<String>,
 }
 
 impl LogMessage {
-    pub fn height(&self) -> usize {
-        if self.amount_out.is_some() {
-            5
-        } else {
-            4
+    // whether a lowercase query occurs in one of the currency names or txids
+    fn matches(&self, query: &str) -> bool {
+        [
+            Some(&self.reserve),
+            Some(&self.amount_in_currency),
+            Some(&self.txid),
+            self.export_txid.as_ref(),
+            self.import_txid.as_ref(),
+        ]
+        .iter()
+        .flatten()
+        .any(|field| field.to_lowercase().contains(query))
+    }
+
+    pub fn is_same_transfer(&self, other: &LogMessage) -> bool {
+        self.chain == other.chain
+            && self.txid == other.txid
+            && self.vout == other.vout
+            // events that are not in a transaction have no txid
+            && self.reserve == other.reserve
+    }
+
+    fn confirmation_label(&self) -> String {
+        match self.confirmations {
+            0 => String::from("mempool"),
+            c if c >= MAX_CONFIRMATIONS => format!("{}+ conf", MAX_CONFIRMATIONS),
+            c => format!("{} conf", c),
         }
     }
+
+    fn description(&self) -> String {
+        match self._type {
+            MessageType::Initiate | MessageType::Settle => {
+                format!("transfer {} for {}", self.state.label(), self.reserve)
+            }
+            MessageType::NewCurrency => format!("new currency {}", self.reserve),
+            MessageType::NewBasket => format!("new basket {}", self.reserve),
+            MessageType::Identity => format!("identity {} updated", self.reserve),
+            MessageType::Notarization => format!("notarization for {}", self.reserve),
+            MessageType::PreconversionEnd => format!("preconversion of {} ended", self.reserve),
+            MessageType::LaunchFailed => {
+                format!(
+                    "launch of {} failed, preconversions are refunded",
+                    self.reserve
+                )
+            }
+        }
+    }
+
+    // the lines of the message, from top to bottom
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "{} {} ({})",
+            self.time,
+            self.description(),
+            self.confirmation_label()
+        )];
+
+        if matches!(self._type, MessageType::Initiate | MessageType::Settle) {
+            lines.push(format!(
+                "{} {}",
+                self.amount_in.as_vrsc(),
+                self.amount_in_currency
+            ));
+
+            if let Some(amount_out) = self.amount_out {
+                lines.push(format!("-> {} {}", amount_out, self.reserve));
+            }
+        }
+
+        lines
+    }
+
+    // the lines of the message and a separator
+    pub fn height(&self) -> usize {
+        self.lines().len() + 1
+    }
 }
 
 #[derive(Clone)]
 pub enum MessageType {
     Initiate,
     Settle,
+    NewCurrency,
+    NewBasket,
+    // an identity was registered or updated
+    Identity,
+    // a notarization of a PBaaS chain
+    Notarization,
+    // a currency left its preconversion phase and launched
+    PreconversionEnd,
+    // a currency left its preconversion phase without launching, its preconversions are refunded
+    LaunchFailed,
+}
+
+/// Where a reserve transfer is between its initiation and its settlement on the destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TransferState {
+    // not yet bundled in an export
+    Pending,
+    Exported,
+    Settled,
+}
+
+impl TransferState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransferState::Pending => "pending",
+            TransferState::Exported => "exported",
+            TransferState::Settled => "settled",
+        }
+    }
 }
@@ -1,7 +1,11 @@
+pub mod basket_dialog;
+pub mod crosschain;
 pub mod filterbox;
 pub mod log;
+pub mod opportunities;
 pub mod pbaas_dialog;
 pub mod reserves;
 pub mod reservetable;
 pub mod selector;
+pub mod tabs;
 pub mod tvl;
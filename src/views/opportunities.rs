@@ -0,0 +1,50 @@
+use cursive::{view::ViewWrapper, views::*, View};
+use tracing::debug;
+
+use crate::verus::arbitrage::Opportunity;
+
+pub struct Opportunities {
+    view: ScrollView<TextView>,
+}
+
+impl Opportunities {
+    pub fn new() -> impl View {
+        Self {
+            view: ScrollView::new(TextView::new(" no opportunities")),
+        }
+    }
+
+    pub fn update(&mut self, opportunities: Vec<Opportunity>) {
+        debug!("update opportunities: {:#?}", opportunities);
+
+        if opportunities.is_empty() {
+            self.view.get_inner_mut().set_content(" no opportunities");
+
+            return;
+        }
+
+        self.view.get_inner_mut().set_content(
+            opportunities
+                .iter()
+                .map(|o| {
+                    format!(
+                        " {}/{}: {:+.3}%\n   sell in {:<max_name_len$} @ {:.8}\n   buy in  {:<max_name_len$} @ {:.8}\n",
+                        o.base,
+                        o.quote,
+                        o.net_spread * 100.0,
+                        o.sell_basket,
+                        o.sell_rate,
+                        o.buy_basket,
+                        o.buy_rate,
+                        max_name_len = 17
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(""),
+        );
+    }
+}
+
+impl ViewWrapper for Opportunities {
+    cursive::wrap_impl!(self.view: ScrollView<TextView>);
+}
@@ -1,33 +1,95 @@
+use std::collections::{HashMap, HashSet};
+
 use cursive::{
     view::{Resizable, ViewWrapper},
     views::*,
     View,
 };
 use tracing::{debug, info};
-use vrsc_rpc::json::Currency;
+use vrsc_rpc::json::{vrsc::Address, Currency};
 
-use crate::{menu::BasketMode, verus::Basket, views::reservetable::ReserveTable};
+use crate::{
+    menu::BasketMode,
+    verus::{valuation::Valuation, Basket},
+    views::{
+        basket_dialog::matches_pattern,
+        reservetable::{PriceMode, ReserveTable},
+    },
+};
 
 pub struct Reserves {
     view: ResizedView<LinearLayout>,
     baskets: Vec<Basket>,
     checked_currencies: Vec<Currency>,
+    // basket names or patterns, used in `BasketMode::Baskets`
+    selected_baskets: Vec<String>,
     basket_mode: BasketMode,
+    basket_sort: BasketSort,
+    baskets_descending: bool,
+    row_sort: RowSort,
+    rows_descending: bool,
+    price_mode: PriceMode,
+    // the reserve that was last used for cross prices
+    cross_reserve: Option<String>,
+    // prices in the native currency of the chain, used to compare baskets
+    valuation: Valuation,
+    // the baskets in the previous block, per basket currency id
+    previous_baskets: HashMap<Address, Basket>,
+    // prices in the native currency of the chain in the previous block
+    previous_valuation: Valuation,
+    // only show the baskets that changed in the last block
+    changed_only: bool,
+    // baskets that are shown above all others, regardless of the sort
+    pinned: HashSet<Address>,
+    // baskets of which only the title line is shown
+    collapsed: HashSet<Address>,
 }
 
 impl Reserves {
     pub fn new() -> impl View {
         Reserves {
-            view: LinearLayout::horizontal().min_width(100),
+            view: LinearLayout::vertical().min_width(100),
             baskets: vec![],
             checked_currencies: vec![],
+            selected_baskets: vec![],
             basket_mode: BasketMode::All,
+            basket_sort: BasketSort::Name,
+            baskets_descending: false,
+            row_sort: RowSort::Name,
+            rows_descending: false,
+            price_mode: PriceMode::BasketInReserve,
+            cross_reserve: None,
+            valuation: Valuation {
+                quote: String::new(),
+                prices: HashMap::new(),
+            },
+            previous_baskets: HashMap::new(),
+            previous_valuation: Valuation {
+                quote: String::new(),
+                prices: HashMap::new(),
+            },
+            changed_only: false,
+            pinned: HashSet::new(),
+            collapsed: HashSet::new(),
         }
     }
 
     // #[instrument(level = "debug", skip(self))]
-    pub fn update_baskets(&mut self, baskets: Vec<Basket>) {
+    pub fn update_baskets(&mut self, baskets: Vec<Basket>, previous_baskets: Vec<Basket>) {
         info!("{} baskets retrieved", baskets.len());
+
+        let native = baskets
+            .iter()
+            .find_map(|b| b.currencynames.get(&b.active_chain_id))
+            .cloned()
+            .unwrap_or_default();
+        self.valuation = Valuation::derive(&baskets, &native, &HashMap::new());
+        self.previous_valuation = Valuation::derive(&previous_baskets, &native, &HashMap::new());
+
+        self.previous_baskets = previous_baskets
+            .into_iter()
+            .map(|basket| (basket.currencyid.clone(), basket))
+            .collect();
         self.baskets = baskets;
     }
 
@@ -37,62 +99,429 @@ impl Reserves {
         self.checked_currencies = checked_currencies;
     }
 
+    pub fn update_selected_baskets(&mut self, selected_baskets: Vec<String>) {
+        debug!("selected_baskets: {:?}", &selected_baskets);
+        self.selected_baskets = selected_baskets;
+    }
+
+    pub fn selected_baskets(&self) -> Vec<String> {
+        self.selected_baskets.clone()
+    }
+
+    pub fn basket_mode(&self) -> BasketMode {
+        self.basket_mode.clone()
+    }
+
     pub fn update_basket_mode(&mut self, b: BasketMode) {
         debug!("updating basket mode to {:?}", b);
         self.basket_mode = b;
     }
 
-    pub fn update_view(&mut self) {
-        self.view.get_inner_mut().clear();
-        self.view.get_inner_mut().add_child(
-            ScrollView::new({
-                let mut ll = LinearLayout::vertical();
-
-                debug!("basket mode: {:?}", self.basket_mode);
-
-                match self.basket_mode {
-                    BasketMode::All => {
-                        let filtered_baskets = self.baskets.iter().filter(|basket| {
-                            basket.currency_state.reservecurrencies.iter().any(|rc| {
-                                self.checked_currencies
-                                    .iter()
-                                    .map(|c| &c.currencydefinition.currencyid)
-                                    .cloned()
-                                    .collect::<Vec<_>>()
-                                    .contains(&rc.currencyid)
-                            })
-                        });
-
-                        debug!("filtered_baskets: {:?}", filtered_baskets);
-
-                        filtered_baskets.for_each(|b| ll.add_child(ReserveTable::new(b.clone())));
-                    }
-                    BasketMode::Selected => {
-                        for mut basket in self.baskets.clone().into_iter() {
-                            // apply the filter from selector
-                            basket.currency_state.reservecurrencies.retain(|rc| {
-                                self.checked_currencies.iter().any(|checked_currency| {
-                                    checked_currency.currencydefinition.currencyid == rc.currencyid
-                                        || rc.currencyid == basket.active_chain_id
-                                })
-                            });
-
-                            debug!(
-                                "basket.currency_state.reservecurrencies: {:?}",
-                                basket.currency_state.reservecurrencies
-                            );
-
-                            if basket.currency_state.reservecurrencies.len() > 1 {
-                                ll.add_child(ReserveTable::new(basket));
-                            }
-                        }
+    pub fn next_basket_sort(&mut self) {
+        self.basket_sort = self.basket_sort.next();
+    }
+
+    pub fn toggle_basket_order(&mut self) {
+        self.baskets_descending = !self.baskets_descending;
+    }
+
+    pub fn next_row_sort(&mut self) {
+        self.row_sort = self.row_sort.next();
+    }
+
+    pub fn toggle_row_order(&mut self) {
+        self.rows_descending = !self.rows_descending;
+    }
+
+    /// Cycles through the price modes; cross prices are against the last chosen reserve, or the native currency.
+    pub fn next_price_mode(&mut self) {
+        self.price_mode = match self.price_mode {
+            PriceMode::BasketInReserve => PriceMode::ReserveInBasket,
+            PriceMode::ReserveInBasket => PriceMode::Cross(
+                self.cross_reserve
+                    .clone()
+                    .unwrap_or_else(|| self.valuation.quote.clone()),
+            ),
+            PriceMode::Cross(_) => PriceMode::BasketInReserve,
+        };
+    }
+
+    pub fn set_price_mode(&mut self, price_mode: PriceMode) {
+        if let PriceMode::Cross(reserve) = &price_mode {
+            self.cross_reserve = Some(reserve.clone());
+        }
+        self.price_mode = price_mode;
+    }
+
+    /// The names of all reserve currencies of the baskets of the chain.
+    pub fn reserve_names(&self) -> Vec<String> {
+        let mut names = self
+            .baskets
+            .iter()
+            .flat_map(|basket| basket.currencynames.values().cloned())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        names.dedup();
+
+        names
+    }
+
+    pub fn toggle_changed_only(&mut self) {
+        self.changed_only = !self.changed_only;
+    }
+
+    // whether the supply or any of the reserves of a basket changed in the last block
+    fn changed(&self, basket: &Basket) -> bool {
+        let previous = match self.previous_baskets.get(&basket.currencyid) {
+            Some(previous) => previous,
+            // a new basket, unless there is no previous block to compare with
+            None => return !self.previous_baskets.is_empty(),
+        };
+
+        // only the reserves that are shown are compared, the basket can be filtered by the checked currencies
+        let shown = |currencyid: &Address| {
+            basket
+                .currency_state
+                .reservecurrencies
+                .iter()
+                .any(|rc| &rc.currencyid == currencyid)
+        };
+        let state = |basket: &Basket| {
+            basket
+                .currency_state
+                .reservecurrencies
+                .iter()
+                .filter(|rc| shown(&rc.currencyid))
+                .map(|rc| {
+                    (
+                        rc.currencyid.clone(),
+                        rc.reserves.as_sat(),
+                        rc.priceinreserve.as_sat(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        basket.currency_state.supply.as_sat() != previous.currency_state.supply.as_sat()
+            || state(basket) != state(previous)
+    }
+
+    pub fn set_pinned(&mut self, currencyid: &Address, pinned: bool) {
+        if pinned {
+            self.pinned.insert(currencyid.clone());
+        } else {
+            self.pinned.remove(currencyid);
+        }
+    }
+
+    pub fn set_collapsed(&mut self, currencyid: &Address, collapsed: bool) {
+        if collapsed {
+            self.collapsed.insert(currencyid.clone());
+        } else {
+            self.collapsed.remove(currencyid);
+        }
+    }
+
+    /// Moves the focus to the table of a basket, if it is shown.
+    pub fn focus_basket(&mut self, currencyid: &Address) {
+        if let Some(list) = self.list_mut() {
+            let index = (0..list.len()).find(|i| {
+                list.get_child(*i)
+                    .and_then(|view| view.downcast_ref::<ReserveTable>())
+                    .map(|table| &table.basket.currencyid == currencyid)
+                    .unwrap_or(false)
+            });
+
+            if let Some(index) = index {
+                let _ = list.set_focus_index(index);
+            }
+        }
+    }
+
+    // the basket of the table that has the focus
+    fn focused_basket(&mut self) -> Option<Address> {
+        let list = self.list_mut()?;
+
+        list.get_child(list.get_focus_index())
+            .and_then(|view| view.downcast_ref::<ReserveTable>())
+            .map(|table| table.basket.currencyid.clone())
+    }
+
+    fn list_mut(&mut self) -> Option<&mut LinearLayout> {
+        self.view
+            .get_inner_mut()
+            .get_child_mut(1)
+            .and_then(|view| view.downcast_mut::<ResizedView<ScrollView<LinearLayout>>>())
+            .map(|resized| resized.get_inner_mut().get_inner_mut())
+    }
+
+    // the baskets to show, with only the reserves to show, for the current basket mode
+    fn filtered_baskets(&self) -> Vec<Basket> {
+        match self.basket_mode {
+            BasketMode::All => {
+                let filtered_baskets = self
+                    .baskets
+                    .iter()
+                    .filter(|basket| {
+                        basket.currency_state.reservecurrencies.iter().any(|rc| {
+                            self.checked_currencies
+                                .iter()
+                                .map(|c| &c.currencydefinition.currencyid)
+                                .cloned()
+                                .collect::<Vec<_>>()
+                                .contains(&rc.currencyid)
+                        })
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                debug!("filtered_baskets: {:?}", filtered_baskets);
+
+                filtered_baskets
+            }
+            BasketMode::Selected => {
+                let mut filtered_baskets = vec![];
+
+                for mut basket in self.baskets.clone().into_iter() {
+                    // apply the filter from selector
+                    basket.currency_state.reservecurrencies.retain(|rc| {
+                        self.checked_currencies.iter().any(|checked_currency| {
+                            checked_currency.currencydefinition.currencyid == rc.currencyid
+                                || rc.currencyid == basket.active_chain_id
+                        })
+                    });
+
+                    debug!(
+                        "basket.currency_state.reservecurrencies: {:?}",
+                        basket.currency_state.reservecurrencies
+                    );
+
+                    if basket.currency_state.reservecurrencies.len() > 1 {
+                        filtered_baskets.push(basket);
                     }
                 }
 
-                ll
+                filtered_baskets
+            }
+            BasketMode::Baskets => self
+                .baskets
+                .iter()
+                .filter(|basket| {
+                    self.selected_baskets
+                        .iter()
+                        .any(|pattern| matches_pattern(pattern, &basket.name))
+                })
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn sort_baskets(&self, baskets: &mut [Basket]) {
+        baskets.sort_by(|a, b| {
+            let ordering = match self.basket_sort {
+                BasketSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                BasketSort::Supply => a
+                    .currency_state
+                    .supply
+                    .as_vrsc()
+                    .total_cmp(&b.currency_state.supply.as_vrsc()),
+                BasketSort::ReserveValue => self.reserve_value(a).total_cmp(&self.reserve_value(b)),
+                BasketSort::ReserveCount => a
+                    .currency_state
+                    .reservecurrencies
+                    .len()
+                    .cmp(&b.currency_state.reservecurrencies.len()),
+                BasketSort::PriceChange => self
+                    .price_change(a)
+                    .unwrap_or_default()
+                    .total_cmp(&self.price_change(b).unwrap_or_default()),
+            };
+
+            if self.baskets_descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    fn sort_rows(&self, basket: &mut Basket) {
+        let names = basket.currencynames.clone();
+        let name = |currencyid: &Address| {
+            names
+                .get(currencyid)
+                .map(|name| name.to_lowercase())
+                .unwrap_or_default()
+        };
+
+        basket.currency_state.reservecurrencies.sort_by(|a, b| {
+            let ordering = match self.row_sort {
+                RowSort::Name => name(&a.currencyid).cmp(&name(&b.currencyid)),
+                RowSort::Price => a
+                    .priceinreserve
+                    .as_vrsc()
+                    .total_cmp(&b.priceinreserve.as_vrsc()),
+                RowSort::Amount => a.reserves.as_vrsc().total_cmp(&b.reserves.as_vrsc()),
+            };
+
+            if self.rows_descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    // the value of all reserves of a basket in the native currency of the chain, as far as they can be priced
+    fn reserve_value(&self, basket: &Basket) -> f64 {
+        basket
+            .currency_state
+            .reservecurrencies
+            .iter()
+            .filter_map(|rc| {
+                basket
+                    .currencynames
+                    .get(&rc.currencyid)
+                    .and_then(|name| self.valuation.value_of(name, rc.reserves.as_vrsc()))
             })
-            .full_width(),
+            .sum()
+    }
+
+    // the relative change of the basket price (in the native currency) since the previous block
+    fn price_change(&self, basket: &Basket) -> Option<f64> {
+        let previous = self.previous_valuation.prices.get(&basket.name)?;
+        let current = self.valuation.prices.get(&basket.name)?;
+
+        if *previous > 0.0 {
+            Some((current - previous) / previous)
+        } else {
+            None
+        }
+    }
+
+    pub fn update_view(&mut self) {
+        let mut baskets = self.filtered_baskets();
+        if self.changed_only {
+            baskets.retain(|basket| self.changed(basket));
+        }
+        self.sort_baskets(&mut baskets);
+        // stable, so the pinned baskets keep the sort among themselves
+        baskets.sort_by_key(|basket| !self.pinned.contains(&basket.currencyid));
+        for basket in baskets.iter_mut() {
+            self.sort_rows(basket);
+        }
+
+        let header = format!(
+            " baskets by {} {} (s/S) | rows by {} {} (r/R) | prices {} (m/M) | enter: collapse, *: pin",
+            self.basket_sort.label(),
+            if self.baskets_descending { "v" } else { "^" },
+            self.row_sort.label(),
+            if self.rows_descending { "v" } else { "^" },
+            self.price_mode.label(),
         );
+        let header = if self.changed_only {
+            format!("{} | changed only (d)", header)
+        } else {
+            header
+        };
+
+        // the tables are replaced in the same scroll view, so the scroll position and the focused basket are kept
+        let focused = self.focused_basket();
+
+        if self.list_mut().is_none() {
+            self.view.get_inner_mut().add_child(TextView::new(""));
+            self.view
+                .get_inner_mut()
+                .add_child(ScrollView::new(LinearLayout::vertical()).full_width());
+        }
+
+        if let Some(header_view) = self
+            .view
+            .get_inner_mut()
+            .get_child_mut(0)
+            .and_then(|view| view.downcast_mut::<TextView>())
+        {
+            header_view.set_content(header);
+        }
+
+        debug!("basket mode: {:?}", self.basket_mode);
+
+        let tables = baskets
+            .into_iter()
+            .map(|b| {
+                let collapsed = self.collapsed.contains(&b.currencyid);
+                let pinned = self.pinned.contains(&b.currencyid);
+                let previous = self.previous_baskets.get(&b.currencyid).cloned();
+                ReserveTable::new(b, previous, collapsed, pinned, self.price_mode.clone())
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(list) = self.list_mut() {
+            list.clear();
+            for table in tables {
+                list.add_child(table);
+            }
+        }
+
+        if let Some(currencyid) = focused {
+            self.focus_basket(&currencyid);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasketSort {
+    Name,
+    Supply,
+    ReserveValue,
+    ReserveCount,
+    PriceChange,
+}
+
+impl BasketSort {
+    fn next(&self) -> Self {
+        match self {
+            BasketSort::Name => BasketSort::Supply,
+            BasketSort::Supply => BasketSort::ReserveValue,
+            BasketSort::ReserveValue => BasketSort::ReserveCount,
+            BasketSort::ReserveCount => BasketSort::PriceChange,
+            BasketSort::PriceChange => BasketSort::Name,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            BasketSort::Name => "name",
+            BasketSort::Supply => "supply",
+            BasketSort::ReserveValue => "reserve value",
+            BasketSort::ReserveCount => "number of reserves",
+            BasketSort::PriceChange => "price change",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowSort {
+    Name,
+    Price,
+    Amount,
+}
+
+impl RowSort {
+    fn next(&self) -> Self {
+        match self {
+            RowSort::Name => RowSort::Price,
+            RowSort::Price => RowSort::Amount,
+            RowSort::Amount => RowSort::Name,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            RowSort::Name => "name",
+            RowSort::Price => "price",
+            RowSort::Amount => "amount",
+        }
     }
 }
 
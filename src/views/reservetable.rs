@@ -1,171 +1,462 @@
-use cursive::{theme::Color, Vec2, View};
+use cursive::{
+    direction::Direction,
+    event::{Callback, Event, EventResult, Key},
+    theme::{BaseColor, Color, ColorStyle},
+    view::CannotFocus,
+    Vec2, View,
+};
 use tracing::debug;
-use vrsc_rpc::json::vrsc::Denomination;
+use vrsc_rpc::json::vrsc::{Address, Denomination};
 
-use crate::verus::Basket;
+use crate::{
+    verus::{
+        arbitrage::{cross_rate, RESERVE_TO_RESERVE_FEE},
+        Basket,
+    },
+    views::reserves::Reserves,
+};
 
+// a column of a reserve table
+struct Column {
+    header: String,
+    cells: Vec<String>,
+    colors: Vec<Option<Color>>,
+    // shows the change of the column before it
+    is_change: bool,
+}
+
+impl Column {
+    fn new(header: String, cells: Vec<String>) -> Self {
+        Column {
+            header,
+            colors: vec![None; cells.len()],
+            cells,
+            is_change: false,
+        }
+    }
+
+    // green with an arrow up for an increase, red with an arrow down for a decrease, empty for no change
+    fn change(changes: Vec<Option<(f64, String)>>) -> Self {
+        let (cells, colors) = changes
+            .into_iter()
+            .map(|change| match change {
+                Some((change, text)) if change > 0.0 => {
+                    (format!("↑{}", text), Some(Color::Dark(BaseColor::Green)))
+                }
+                Some((change, text)) if change < 0.0 => {
+                    (format!("↓{}", text), Some(Color::Dark(BaseColor::Red)))
+                }
+                _ => (String::new(), None),
+            })
+            .unzip();
+
+        Column {
+            header: String::new(),
+            cells,
+            colors,
+            is_change: true,
+        }
+    }
+
+    // as wide as the widest cell or the header
+    fn width(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|cell| cell.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(self.header.len())
+    }
+}
+
+// enter / space: collapse or expand, left / right: collapse / expand, *: pin to the top of the reserves panel
 pub struct ReserveTable {
     pub basket: Basket,
+    // the state of the basket in the previous block, to show what changed
+    previous: Option<Basket>,
+    // only the title line is shown
+    collapsed: bool,
+    pinned: bool,
+    price_mode: PriceMode,
 }
 
-// -- papa.v2 -------------------------------------------------------------------------------------------- Price ------------ Weight
-// VRSCTEST                                                                                  1756739166.70826087 |      202.02656542
-// v2                                                                                       11110314722.06983757 | 44443705.11234567
+/// What the Price column of a reserve table shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriceMode {
+    // the price of the basket currency in each reserve
+    BasketInReserve,
+    // the price of each reserve in the basket currency
+    ReserveInBasket,
+    // the price of each reserve in the given reserve currency, if the basket holds it
+    Cross(String),
+}
+
+impl PriceMode {
+    pub fn label(&self) -> String {
+        match self {
+            PriceMode::BasketInReserve => String::from("basket in reserve"),
+            PriceMode::ReserveInBasket => String::from("reserve in basket"),
+            PriceMode::Cross(reserve) => format!("in {}", reserve),
+        }
+    }
+
+    fn header(&self) -> String {
+        match self {
+            PriceMode::BasketInReserve | PriceMode::ReserveInBasket => String::from("Price"),
+            PriceMode::Cross(reserve) => format!("Price in {}", reserve),
+        }
+    }
+
+    fn price(&self, basket: &Basket, name: &str, price_in_reserve: f64) -> Option<f64> {
+        match self {
+            PriceMode::BasketInReserve => Some(price_in_reserve),
+            PriceMode::ReserveInBasket => {
+                Some(1.0 / price_in_reserve).filter(|_| price_in_reserve > 0.0)
+            }
+            PriceMode::Cross(reserve) => cross_rate(basket, name, reserve),
+        }
+    }
+}
+
+//  - papa.v2 (1000.00000000) -------------------------------------- Price ------------- Amount -- Weight
+//    system VRSCTEST, fee 0.025% (0.05% between reserves)
+//  VRSCTEST                                          1756739166.70826087 ↑0.10% |  202.02656542 ↓0.20000000 | 50.00%
+//  v2                                               11110314722.06983757        | 4444.11234567             | 50.00%
 impl View for ReserveTable {
     fn draw(&self, printer: &cursive::Printer) {
-        let biggest_number_weight = self
-            .basket
-            .currency_state
-            .reservecurrencies
+        if self.collapsed {
+            self.draw_summary(printer);
+            return;
+        }
+
+        debug!("{:#?}", self.basket.currency_state.reservecurrencies);
+
+        let reservecurrencies = &self.basket.currency_state.reservecurrencies;
+        let names = reservecurrencies
             .iter()
-            .map(|rc| {
-                rc.reserves
-                    .as_sat()
-                    .to_string()
-                    .chars()
-                    .collect::<Vec<_>>()
-                    .len()
+            .map(|rc| self.reserve_name(&rc.currencyid))
+            .collect::<Vec<_>>();
+
+        let prices = reservecurrencies
+            .iter()
+            .zip(names.iter())
+            .map(|(rc, name)| {
+                self.price_mode
+                    .price(&self.basket, name, rc.priceinreserve.as_vrsc())
             })
-            .max()
-            .unwrap()
-            + 1;
+            .collect::<Vec<_>>();
+        let previous_prices = reservecurrencies
+            .iter()
+            .zip(names.iter())
+            .map(|(rc, name)| {
+                self.previous_reserve(&rc.currencyid)
+                    .and_then(|(previous, price, _)| self.price_mode.price(previous, name, price))
+            })
+            .collect::<Vec<_>>();
 
-        debug!("{:#?}", self.basket.currency_state.reservecurrencies);
-        debug!(
-            "biggest number weight {}: {}",
-            self.basket.name, biggest_number_weight
-        );
+        // the change of the price since the previous block, relative
+        let price_changes = prices
+            .iter()
+            .zip(previous_prices)
+            .map(|(price, previous)| match (price, previous) {
+                (Some(price), Some(previous)) if previous > 0.0 => {
+                    Some((price - previous) / previous)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
 
-        let biggest_number_price = self
-            .basket
-            .currency_state
-            .reservecurrencies
+        // the change of the amount in reserve since the previous block, absolute
+        let amount_changes = reservecurrencies
             .iter()
             .map(|rc| {
-                rc.priceinreserve
-                    .as_sat()
-                    .to_string()
-                    .chars()
-                    .collect::<Vec<_>>()
-                    .len()
+                self.previous_reserve(&rc.currencyid)
+                    .map(|(_, _, amount)| rc.reserves.as_vrsc() - amount)
             })
-            .max()
-            .unwrap()
-            + 1;
+            .collect::<Vec<_>>();
+
+        let columns = [
+            Column::new(
+                self.price_mode.header(),
+                prices
+                    .iter()
+                    .map(|price| {
+                        price
+                            .map(|price| format!("{:.8}", price))
+                            .unwrap_or_else(|| String::from("-"))
+                    })
+                    .collect(),
+            ),
+            Column::change(
+                price_changes
+                    .iter()
+                    .map(|change| {
+                        change.map(|change| (change, format!("{:.2}%", change.abs() * 100.0)))
+                    })
+                    .collect(),
+            ),
+            Column::new(
+                String::from("Amount"),
+                reservecurrencies
+                    .iter()
+                    .map(|rc| format!("{:.8}", rc.reserves.as_vrsc()))
+                    .collect(),
+            ),
+            Column::change(
+                amount_changes
+                    .iter()
+                    .map(|change| change.map(|change| (change, format!("{:.8}", change.abs()))))
+                    .collect(),
+            ),
+            Column::new(
+                String::from("Weight"),
+                reservecurrencies
+                    .iter()
+                    .map(|rc| format!("{:.2}%", rc.weight * 100.0))
+                    .collect(),
+            ),
+        ];
 
-        debug!("biggest number price: {}", biggest_number_price);
+        // the columns are aligned to the right edge, separated by " | ";
+        // a change column directly follows the column it belongs to, and is left out when nothing changed
+        let mut positions = vec![0; columns.len()];
+        let mut x = printer.output_size.x;
+        for (i, column) in columns.iter().enumerate().rev() {
+            if column.width() == 0 {
+                positions[i] = x;
+                continue;
+            }
+
+            x = x.saturating_sub(column.width());
+            positions[i] = x;
+
+            if i > 0 {
+                x = x.saturating_sub(if column.is_change { 1 } else { 3 });
+            }
+        }
 
         // title draw:
-        // two dashes:
-        printer.print((0, 0), " -> ");
+        self.draw_marker(printer);
 
         let supply = &self.basket.currency_state.supply;
         let str_supply = supply.to_string_in(Denomination::Verus);
-
-        let bolp = &self.basket.name.len() + str_supply.len() + 8;
+        let title = format!("{} ({})", &self.basket.name, str_supply);
 
         printer.with_color(Color::from_256colors(32).into(), |printer| {
-            printer.print((4, 0), &format!("{} ({})", &self.basket.name, str_supply));
+            printer.print((4, 0), &title);
         });
 
-        for i in (bolp)
-            ..(printer
-                .output_size
-                .x
-                .saturating_sub(biggest_number_weight + 10))
-        {
+        for i in (title.len() + 5)..printer.output_size.x {
             printer.print((i, 0), "-");
         }
+
         printer.with_color(Color::from_256colors(32).into(), |printer| {
-            printer.print(
-                (
-                    printer
-                        .output_size
-                        .x
-                        .saturating_sub(biggest_number_weight + 9),
-                    0,
-                ),
-                "Price",
-            );
+            for (column, x) in columns.iter().zip(positions.iter()) {
+                if column.header.is_empty() {
+                    continue;
+                }
+
+                // right aligned, with a space on both sides
+                printer.print(
+                    (
+                        (x + column.width() - column.header.len()).saturating_sub(1),
+                        0,
+                    ),
+                    &format!(" {} ", column.header),
+                );
+            }
         });
 
-        for i in printer
-            .output_size
-            .x
-            .saturating_sub(biggest_number_weight + 3)
-            ..printer.output_size.x.saturating_sub(7)
-        {
-            printer.print((i, 0), "-");
-        }
+        printer.print((4, 1), &self.metadata());
 
-        printer.with_color(Color::from_256colors(32).into(), |printer| {
-            printer.print((printer.output_size.x.saturating_sub(8), 0), " Amount ");
-        });
+        for (i, name) in names.iter().enumerate() {
+            let y = i + 2;
 
-        for (i, rc) in self
-            .basket
-            .currency_state
-            .reservecurrencies
-            .iter()
-            .enumerate()
-        {
-            printer.print(
-                (0, i + 1),
-                &format!(
-                    " {}",
-                    self.basket
-                        .currencynames
-                        .get(&rc.currencyid)
-                        .unwrap_or(&rc.currencyid.to_string()),
-                ),
-            );
-
-            printer.print(
-                (
-                    printer
-                        .output_size
-                        .x
-                        .saturating_sub(biggest_number_weight + 4 + biggest_number_price),
-                    i + 1,
-                ),
-                &format!(
-                    "{number:prec$.8}",
-                    prec = biggest_number_price,
-                    number = rc.priceinreserve.as_vrsc()
-                ),
-            );
-
-            printer.print(
-                (
-                    printer
-                        .output_size
-                        .x
-                        .saturating_sub(biggest_number_weight + 4),
-                    i + 1,
-                ),
-                &format!(
-                    " | {number:prec$.8}",
-                    prec = biggest_number_weight,
-                    number = rc.reserves.as_vrsc()
-                ),
-            );
+            printer.print((0, y), &format!(" {}", name));
+
+            for (j, (column, x)) in columns.iter().zip(positions.iter()).enumerate() {
+                if column.width() == 0 {
+                    continue;
+                }
+
+                if !column.is_change && j > 0 {
+                    printer.print((x.saturating_sub(3), y), " | ");
+                }
+
+                let cell = format!("{:>w$}", column.cells[i], w = column.width());
+                match column.colors[i] {
+                    Some(color) => {
+                        printer.with_color(color.into(), |printer| printer.print((*x, y), &cell))
+                    }
+                    None => printer.print((*x, y), &cell),
+                }
+            }
         }
     }
     // when drawing this table, i need to know how many currencies to show in order to calculate the required height of the view.
     // that means that this table needs to have state on which currencies it should show.
     // that means that the initiation of this table should accept a list of currencies.
     fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
+        if self.collapsed {
+            return Vec2::new(3, 1);
+        }
+
         // account for filter?
-        Vec2::new(3, self.basket.currency_state.reservecurrencies.len() + 2) // 1 for title, 1 for blank space below
+        Vec2::new(3, self.basket.currency_state.reservecurrencies.len() + 3) // 1 for title, 1 for metadata, 1 for blank space below
     }
 
-    fn needs_relayout(&self) -> bool {
-        false
+    fn take_focus(&mut self, _source: Direction) -> Result<EventResult, CannotFocus> {
+        Ok(EventResult::Consumed(None))
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::Key(Key::Enter) | Event::Char(' ') => self.set_collapsed(!self.collapsed),
+            Event::Key(Key::Left) if !self.collapsed => self.set_collapsed(true),
+            Event::Key(Key::Right) if self.collapsed => self.set_collapsed(false),
+            Event::Char('*') => {
+                self.pinned = !self.pinned;
+
+                // pinning changes the order of the baskets, so the reserves panel has to be rebuilt
+                let currencyid = self.basket.currencyid.clone();
+                let pinned = self.pinned;
+                EventResult::Consumed(Some(Callback::from_fn(move |siv| {
+                    siv.call_on_name("RESERVES", |view: &mut Reserves| {
+                        view.set_pinned(&currencyid, pinned);
+                        view.update_view();
+                        view.focus_basket(&currencyid);
+                    });
+                })))
+            }
+            _ => EventResult::Ignored,
+        }
     }
 }
 
 impl ReserveTable {
-    pub fn new(basket: Basket) -> Self {
-        ReserveTable { basket }
+    pub fn new(
+        basket: Basket,
+        previous: Option<Basket>,
+        collapsed: bool,
+        pinned: bool,
+        price_mode: PriceMode,
+    ) -> Self {
+        ReserveTable {
+            basket,
+            previous,
+            collapsed,
+            pinned,
+            price_mode,
+        }
+    }
+
+    fn reserve_name(&self, currencyid: &Address) -> String {
+        self.basket
+            .currencynames
+            .get(currencyid)
+            .cloned()
+            .unwrap_or_else(|| currencyid.to_string())
+    }
+
+    // the previous state of the basket, with the previous price in and amount of one of its reserves
+    fn previous_reserve(&self, currencyid: &Address) -> Option<(&Basket, f64, f64)> {
+        let previous = self.previous.as_ref()?;
+
+        previous
+            .currency_state
+            .reservecurrencies
+            .iter()
+            .find(|rc| &rc.currencyid == currencyid)
+            .map(|rc| (previous, rc.priceinreserve.as_vrsc(), rc.reserves.as_vrsc()))
+    }
+
+    fn set_collapsed(&mut self, collapsed: bool) -> EventResult {
+        self.collapsed = collapsed;
+
+        // the reserves panel remembers the collapsed baskets for when it is rebuilt
+        let currencyid = self.basket.currencyid.clone();
+        EventResult::Consumed(Some(Callback::from_fn(move |siv| {
+            siv.call_on_name("RESERVES", |view: &mut Reserves| {
+                view.set_collapsed(&currencyid, collapsed)
+            });
+        })))
+    }
+
+    // `*` for a pinned basket, `-` for an expanded and `+` for a collapsed one.
+    // The marker is highlighted when the table has the focus.
+    fn draw_marker(&self, printer: &cursive::Printer) {
+        let marker = format!(
+            "{}{} ",
+            if self.pinned { '*' } else { ' ' },
+            if self.collapsed { '+' } else { '-' }
+        );
+
+        if printer.focused {
+            printer.with_color(ColorStyle::highlight(), |printer| {
+                printer.print((0, 0), &marker)
+            });
+        } else {
+            printer.print((0, 0), &marker);
+        }
+    }
+
+    // the chain the basket lives on, the chain it was launched from, whether it is a bridge and its conversion fees
+    fn metadata(&self) -> String {
+        let mut metadata = format!("system {}", self.basket.system);
+
+        if let Some(launch_system) = self
+            .basket
+            .launch_system
+            .as_ref()
+            .filter(|launch_system| **launch_system != self.basket.system)
+        {
+            metadata.push_str(&format!(", launched from {}", launch_system));
+        }
+
+        if self.basket.bridge {
+            metadata.push_str(", bridge");
+        }
+
+        // the fees are the same for every basket, only a basket with several reserves converts between reserves
+        metadata.push_str(&format!(
+            ", fee {:.3}%",
+            RESERVE_TO_RESERVE_FEE / 2.0 * 100.0
+        ));
+        if self.basket.currency_state.reservecurrencies.len() > 1 {
+            metadata.push_str(&format!(
+                " ({:.2}% between reserves)",
+                RESERVE_TO_RESERVE_FEE * 100.0
+            ));
+        }
+
+        metadata
+    }
+
+    // one line: the name and supply of the basket and the number of reserves
+    fn draw_summary(&self, printer: &cursive::Printer) {
+        self.draw_marker(printer);
+
+        let str_supply = self
+            .basket
+            .currency_state
+            .supply
+            .to_string_in(Denomination::Verus);
+        let title = format!("{} ({})", &self.basket.name, str_supply);
+
+        printer.with_color(Color::from_256colors(32).into(), |printer| {
+            printer.print((4, 0), &title);
+        });
+
+        let reserves = format!(
+            " {} reserves ",
+            self.basket.currency_state.reservecurrencies.len()
+        );
+        let reserves_start = printer.output_size.x.saturating_sub(reserves.len());
+
+        for i in (title.len() + 5)..reserves_start {
+            printer.print((i, 0), "-");
+        }
+
+        printer.print((reserves_start, 0), &reserves);
     }
 }
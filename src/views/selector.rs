@@ -1,46 +1,215 @@
-use std::sync::mpsc::Sender;
+use std::{collections::HashSet, sync::mpsc::Sender};
 
 use cursive::{
-    view::{Nameable, ViewWrapper},
+    event::{Event, EventResult, Key},
+    view::{Finder, Nameable, Resizable, ViewWrapper},
     views::*,
     View,
 };
 use tracing::debug;
-use vrsc_rpc::json::Currency;
+use vrsc_rpc::json::{vrsc::Address, Currency};
 
 use crate::controller::ControllerMessage;
 
 use super::filterbox::FilterBox;
 
+// ctrl-a: check all listed currencies, ctrl-d: uncheck them, ctrl-r: invert them, / : search
 pub struct Selector {
-    view: ScrollView<LinearLayout>,
+    // the search box, the selection buttons and the list of currencies
+    view: LinearLayout,
     c_tx: Sender<ControllerMessage>,
+    currencies: Vec<Currency>,
+    checked: HashSet<Address>,
+    query: String,
 }
 
 impl Selector {
     pub fn new(c_tx: Sender<ControllerMessage>) -> impl View {
+        let search = EditView::new().on_edit(|siv, query, _| {
+            siv.call_on_name("SELECTOR", |selector: &mut Selector| {
+                selector.set_query(query)
+            });
+        });
+
+        let buttons = LinearLayout::horizontal()
+            .child(Button::new("All", |siv| {
+                siv.call_on_name("SELECTOR", |selector: &mut Selector| selector.select_all());
+            }))
+            .child(Button::new("None", |siv| {
+                siv.call_on_name("SELECTOR", |selector: &mut Selector| selector.select_none());
+            }))
+            .child(Button::new("Invert", |siv| {
+                siv.call_on_name("SELECTOR", |selector: &mut Selector| {
+                    selector.invert_selection()
+                });
+            }));
+
         Selector {
-            view: ScrollView::new(LinearLayout::vertical()),
+            view: LinearLayout::vertical()
+                .child(search.with_name("selector_search").full_width())
+                .child(buttons)
+                .child(ScrollView::new(LinearLayout::vertical())),
             c_tx,
+            currencies: vec![],
+            checked: HashSet::new(),
+            query: String::new(),
         }
     }
 
     pub fn update(&mut self, reserve_currencies: Vec<Currency>) {
         debug!("update selector overview");
 
-        if !self.view.get_inner().is_empty() {
-            self.view.get_inner_mut().set_focus_index(0).unwrap();
+        self.currencies = reserve_currencies;
+        self.update_list();
+    }
+
+    pub fn checked_currencies(&self) -> Vec<Currency> {
+        self.currencies
+            .iter()
+            .filter(|c| self.checked.contains(&c.currencydefinition.currencyid))
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_checked(&mut self, currencyid: &Address, checked: bool) {
+        if checked {
+            self.checked.insert(currencyid.clone());
+        } else {
+            self.checked.remove(currencyid);
+        }
+    }
+
+    /// Checks exactly the currencies with the given fully qualified names.
+    pub fn check_only(&mut self, names: &[String]) {
+        self.checked = self
+            .currencies
+            .iter()
+            .filter(|c| names.contains(&c.currencydefinition.fullyqualifiedname))
+            .map(|c| c.currencydefinition.currencyid.clone())
+            .collect();
+
+        self.update_list();
+    }
+
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_lowercase();
+        self.update_list();
+    }
+
+    pub fn select_all(&mut self) {
+        let listed = self.listed_currencyids();
+        self.checked.extend(listed);
+        self.selection_changed();
+    }
+
+    pub fn select_none(&mut self) {
+        for currencyid in self.listed_currencyids() {
+            self.checked.remove(&currencyid);
         }
+        self.selection_changed();
+    }
 
-        self.view.get_inner_mut().clear();
-        for rc in reserve_currencies {
-            self.view
-                .get_inner_mut()
-                .add_child(FilterBox::new(rc, self.c_tx.clone()).with_name("filterbox"));
+    pub fn invert_selection(&mut self) {
+        for currencyid in self.listed_currencyids() {
+            if !self.checked.remove(&currencyid) {
+                self.checked.insert(currencyid);
+            }
         }
+        self.selection_changed();
+    }
+
+    fn selection_changed(&mut self) {
+        self.update_list();
+
+        self.c_tx
+            .send(ControllerMessage::CurrencySelectionChange)
+            .unwrap();
+    }
+
+    // the currencies that match the search query
+    fn listed_currencyids(&self) -> Vec<Address> {
+        self.currencies
+            .iter()
+            .filter(|c| self.matches(c))
+            .map(|c| c.currencydefinition.currencyid.clone())
+            .collect()
+    }
+
+    fn matches(&self, currency: &Currency) -> bool {
+        currency
+            .currencydefinition
+            .fullyqualifiedname
+            .to_lowercase()
+            .contains(&self.query)
+    }
+
+    fn update_list(&mut self) {
+        let filter_boxes = self
+            .currencies
+            .iter()
+            .filter(|c| self.matches(c))
+            .map(|c| {
+                FilterBox::new(
+                    c.clone(),
+                    self.checked.contains(&c.currencydefinition.currencyid),
+                    self.c_tx.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let list = self.list_mut();
+        if !list.is_empty() {
+            list.set_focus_index(0).unwrap();
+        }
+
+        list.clear();
+        for filter_box in filter_boxes {
+            list.add_child(filter_box.with_name("filterbox"));
+        }
+    }
+
+    fn list_mut(&mut self) -> &mut LinearLayout {
+        self.view
+            .get_child_mut(2)
+            .and_then(|view| view.downcast_mut::<ScrollView<LinearLayout>>())
+            .map(|scroll_view| scroll_view.get_inner_mut())
+            .expect("the third child of the selector is the list of currencies")
     }
 }
 
 impl ViewWrapper for Selector {
-    cursive::wrap_impl!(self.view: ScrollView<LinearLayout>);
+    cursive::wrap_impl!(self.view: LinearLayout);
+
+    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+        match event {
+            Event::CtrlChar('a') => {
+                self.select_all();
+                EventResult::Consumed(None)
+            }
+            Event::CtrlChar('d') => {
+                self.select_none();
+                EventResult::Consumed(None)
+            }
+            Event::CtrlChar('r') => {
+                self.invert_selection();
+                EventResult::Consumed(None)
+            }
+            Event::Key(Key::Esc) if !self.query.is_empty() => {
+                self.view
+                    .call_on_name("selector_search", |search: &mut EditView| {
+                        let _ = search.set_content("");
+                    });
+                self.set_query("");
+                EventResult::Consumed(None)
+            }
+            event => match self.view.on_event(event.clone()) {
+                EventResult::Ignored if event == Event::Char('/') => self
+                    .view
+                    .set_focus_index(0)
+                    .map(|_| EventResult::Consumed(None))
+                    .unwrap_or(EventResult::Ignored),
+                result => result,
+            },
+        }
+    }
 }
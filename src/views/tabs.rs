@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use cursive::{theme::Color, Printer, Vec2, View};
+
+/// A single line with the names of all running chains, in which the active chain is highlighted.
+/// Chains that received a block since they were last shown are marked with a `*`.
+pub struct ChainTabs {
+    chains: Vec<String>,
+    active: String,
+    updated: HashSet<String>,
+}
+
+impl ChainTabs {
+    pub fn new() -> Self {
+        ChainTabs {
+            chains: vec![],
+            active: String::new(),
+            updated: HashSet::new(),
+        }
+    }
+
+    pub fn set_chains(&mut self, chains: Vec<String>, active: String) {
+        self.chains = chains;
+        self.set_active(active);
+    }
+
+    pub fn set_active(&mut self, active: String) {
+        self.updated.remove(&active);
+        self.active = active;
+    }
+
+    pub fn mark_updated(&mut self, chain: String) {
+        if chain != self.active {
+            self.updated.insert(chain);
+        }
+    }
+}
+
+impl Default for ChainTabs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl View for ChainTabs {
+    fn draw(&self, printer: &Printer) {
+        let mut x = 0;
+
+        for chain in self.chains.iter() {
+            let label = if self.updated.contains(chain) {
+                format!(" {}* ", chain)
+            } else {
+                format!(" {} ", chain)
+            };
+
+            if chain == &self.active {
+                printer.with_color(Color::from_256colors(32).into(), |printer| {
+                    printer.print((x, 0), &label);
+                });
+            } else {
+                printer.print((x, 0), &label);
+            }
+
+            x += label.len();
+            printer.print((x, 0), "|");
+            x += 1;
+        }
+
+        printer.print((x + 1, 0), "[ / ] to switch chains");
+    }
+
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        Vec2::new(
+            self.chains.iter().map(|c| c.len() + 4).sum::<usize>() + 24,
+            1,
+        )
+    }
+}
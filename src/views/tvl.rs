@@ -1,47 +1,199 @@
-use std::collections::BTreeMap;
+use std::cmp::Ordering;
 
-use cursive::{view::ViewWrapper, views::*, View};
+use cursive::{
+    align::HAlign,
+    view::{Resizable, ViewWrapper},
+    views::*,
+    Cursive, View,
+};
+use cursive_table_view::{TableView, TableViewItem};
 use tracing::debug;
+
+use crate::verus::{tvl::Tvl, valuation::Valuation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TvlColumn {
+    Currency,
+    Amount,
+    Value,
+    Share,
+    Baskets,
+}
+
+#[derive(Debug, Clone)]
+pub struct TvlRow {
+    currency: String,
+    amount: f64,
+    // the value in the quote currency, if the currency has a price
+    value: Option<f64>,
+    // the share of the total value, if the currency has a price
+    share: Option<f64>,
+    // the baskets that hold this currency, with the amount they hold
+    holders: Vec<(String, f64)>,
+}
+
+impl TableViewItem<TvlColumn> for TvlRow {
+    fn to_column(&self, column: TvlColumn) -> String {
+        match column {
+            TvlColumn::Currency => self.currency.clone(),
+            TvlColumn::Amount => format!("{:.8}", self.amount),
+            TvlColumn::Value => self
+                .value
+                .map(|value| format!("{:.2}", value))
+                .unwrap_or_else(|| String::from("-")),
+            TvlColumn::Share => self
+                .share
+                .map(|share| format!("{:.2}%", share * 100.0))
+                .unwrap_or_else(|| String::from("-")),
+            TvlColumn::Baskets => self.holders.len().to_string(),
+        }
+    }
+
+    fn cmp(&self, other: &Self, column: TvlColumn) -> Ordering
+    where
+        Self: Sized,
+    {
+        match column {
+            TvlColumn::Currency => self
+                .currency
+                .to_lowercase()
+                .cmp(&other.currency.to_lowercase()),
+            TvlColumn::Amount => self.amount.total_cmp(&other.amount),
+            TvlColumn::Value => cmp_option(self.value, other.value),
+            TvlColumn::Share => cmp_option(self.share, other.share),
+            TvlColumn::Baskets => self.holders.len().cmp(&other.holders.len()),
+        }
+    }
+}
+
+// currencies without a price are sorted below the ones that have one
+fn cmp_option(a: Option<f64>, b: Option<f64>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
+
 pub struct TVL {
-    view: ScrollView<TextView>,
+    // the table, with the grand total below it
+    view: LinearLayout,
 }
 
 impl TVL {
     pub fn new() -> impl View {
+        let mut table = TableView::<TvlRow, TvlColumn>::new()
+            .column(TvlColumn::Currency, "Currency", |c| c.width(18))
+            .column(TvlColumn::Amount, "Amount", |c| {
+                c.align(HAlign::Right).width(18)
+            })
+            .column(TvlColumn::Value, "Value", |c| {
+                c.align(HAlign::Right).width(14)
+            })
+            .column(TvlColumn::Share, "Share", |c| {
+                c.align(HAlign::Right).width(8)
+            })
+            .column(TvlColumn::Baskets, "#", |c| c.align(HAlign::Right).width(3))
+            .default_column(TvlColumn::Currency);
+
+        table.set_on_submit(|siv: &mut Cursive, _row: usize, index: usize| {
+            let row = siv
+                .call_on_name("TVL", |tvl: &mut TVL| {
+                    tvl.table_mut().borrow_item(index).cloned()
+                })
+                .flatten();
+
+            if let Some(row) = row {
+                siv.add_layer(
+                    Dialog::around(ScrollView::new(TextView::new(basket_breakdown(&row))))
+                        .title(format!("{} per basket", row.currency))
+                        .dismiss_button("Close"),
+                );
+            }
+        });
+
         Self {
-            view: ScrollView::new(TextView::new("0.0 VRSC")),
+            view: LinearLayout::vertical()
+                .child(table.min_size((65, 10)))
+                .child(TextView::new("")),
         }
     }
 
-    pub fn update(&mut self, currencies: BTreeMap<String, f64>) {
-        debug!("update TVL: {:#?}", currencies);
+    pub fn update(&mut self, tvl: Tvl, valuation: Option<Valuation>) {
+        debug!("update TVL: {:#?}", tvl);
 
-        let mut sorted_currencies = Vec::from_iter(currencies);
-        sorted_currencies.sort_by(|currency_a, currency_b| {
-            currency_a
-                .0
-                .to_lowercase()
-                .cmp(&currency_b.0.to_lowercase())
-        });
+        let values = tvl
+            .per_reserve
+            .iter()
+            .map(|(currency, amount)| {
+                valuation
+                    .as_ref()
+                    .and_then(|valuation| valuation.value_of(currency, *amount))
+            })
+            .collect::<Vec<_>>();
+
+        // currencies without a price are left out of the total
+        let total: f64 = values.iter().flatten().sum();
+
+        let rows = tvl
+            .per_reserve
+            .iter()
+            .zip(values)
+            .map(|((currency, amount), value)| TvlRow {
+                currency: currency.clone(),
+                amount: *amount,
+                value,
+                share: value.filter(|_| total > 0.0).map(|value| value / total),
+                holders: tvl.baskets_holding(currency),
+            })
+            .collect::<Vec<_>>();
 
-        self.view.get_inner_mut().set_content(format!(
-            "{}",
-            sorted_currencies
-                .iter()
-                .map(|(k, v)| format!(
-                    " {:<max_name_len$}: {value:>max$.*} \n",
-                    k,
-                    8,
-                    max_name_len = 17,
-                    value = v,
-                    max = 17
-                ))
-                .collect::<Vec<String>>()
-                .join("")
-        ));
+        self.table_mut().set_items(rows);
+
+        let total_line = match &valuation {
+            Some(valuation) => format!(" Total: {:.2} {}", total, valuation.quote),
+            None => String::new(),
+        };
+
+        if let Some(text) = self
+            .view
+            .get_child_mut(1)
+            .and_then(|view| view.downcast_mut::<TextView>())
+        {
+            text.set_content(total_line);
+        }
+    }
+
+    fn table_mut(&mut self) -> &mut TableView<TvlRow, TvlColumn> {
+        self.view
+            .get_child_mut(0)
+            .and_then(|view| view.downcast_mut::<ResizedView<TableView<TvlRow, TvlColumn>>>())
+            .map(|resized| resized.get_inner_mut())
+            .expect("the first child of the TVL view is its table")
     }
 }
 
+fn basket_breakdown(row: &TvlRow) -> String {
+    let mut holders = row.holders.clone();
+    holders.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    holders
+        .iter()
+        .map(|(basket, amount)| {
+            format!(
+                " {:<max_name_len$}: {value:>max$.8} ({:.2}%)\n",
+                basket,
+                amount / row.amount * 100.0,
+                max_name_len = 17,
+                value = amount,
+                max = 17
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("")
+}
+
 impl ViewWrapper for TVL {
-    cursive::wrap_impl!(self.view: ScrollView<TextView>);
+    cursive::wrap_impl!(self.view: LinearLayout);
 }